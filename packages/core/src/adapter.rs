@@ -1,8 +1,9 @@
 use crate::{
     bot::Bot,
-    context::Context,
+    context::{Context, listener::EmitMode},
     error::FrameworkResult,
     message::MessageElement,
+    session::SessionEvent,
     types::{Channel, Guild, GuildMember, GuildRole, Login, LoginStatus, Message, User},
 };
 use async_trait::async_trait;
@@ -10,7 +11,7 @@ use futures_util::StreamExt;
 use serde::Deserialize;
 use std::sync::Arc;
 use tokio::net::TcpStream;
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite::Message as WsMessage};
 
 use url::Url;
 
@@ -263,15 +264,46 @@ where
     /// WebSocket连接成功后建立的回调函数
     async fn accept(&self);
 
+    /// 收到 WebSocket 消息时的回调函数
+    ///
+    /// 适配器在此处理协议帧（如心跳、鉴权响应等），
+    /// 若该消息携带需要分发的事件，则返回解析出的 `SessionEvent`。
+    /// 默认忽略所有消息，不分发任何事件。
+    async fn receive(&self, _message: WsMessage) -> FrameworkResult<Option<SessionEvent>> {
+        Ok(None)
+    }
+
     /// 设置status
+    ///
+    /// `start` 在调用此方法的同时会更新 Bot 的登录状态。
     fn set_status(&self, status: LoginStatus);
 
     /// 获取适配器的状态
     fn get_active(&self) -> bool;
 
+    /// 建立连接并持续接收消息，断开后按配置重连
+    ///
+    /// 连接建立后在上下文上发射 `bot-connect` 事件，连接断开时发射 `bot-disconnect` 事件，
+    /// 参数均为 `Arc<Bot>`。
     async fn start(&self) {
         let mut retry_count = 0;
         let ws_config = self.config();
+        let bot = self.bot();
+        // 适配器状态与 Bot 的登录状态保持同步，Bot 会据此发射 login-added / login-updated 事件
+        let update_status = async |status: LoginStatus| {
+            self.set_status(status);
+            bot.set_status(status).await;
+        };
+        let emit_bot_event = async |event_name: &str| {
+            self.ctx()
+                .emit_async(
+                    event_name,
+                    None,
+                    vec![Box::new(Arc::clone(&bot))],
+                    EmitMode::Serial,
+                )
+                .await;
+        };
 
         loop {
             if !self.get_active() {
@@ -279,7 +311,7 @@ where
                     "Adapter {} is not active, stopping connection attempts.",
                     self.get_name()
                 );
-                self.set_status(LoginStatus::Offline);
+                update_status(LoginStatus::Offline).await;
                 return;
             }
 
@@ -291,12 +323,13 @@ where
 
             let mut socket_stream = match self.prepare().await {
                 Ok((stream, _url)) => {
-                    self.set_status(LoginStatus::Online);
+                    update_status(LoginStatus::Online).await;
                     tracing::info!("Adapter {} connected successfully.", self.get_name());
                     if retry_count > 0 {
                         retry_count = 0;
                     }
                     self.accept().await;
+                    emit_bot_event("bot-connect").await;
                     stream
                 }
                 Err(e) => {
@@ -312,7 +345,7 @@ where
                                 self.get_name(),
                                 ws_config.retry_times
                             );
-                            self.set_status(LoginStatus::Offline);
+                            update_status(LoginStatus::Offline).await;
                             return;
                         }
                         if retry_count == ws_config.retry_times {
@@ -328,7 +361,7 @@ where
                     };
 
                     retry_count += 1;
-                    self.set_status(LoginStatus::Reconnect);
+                    update_status(LoginStatus::Reconnect).await;
                     tracing::info!(
                         "Adapter {} will retry connection in {}ms (attempt {}).",
                         self.get_name(),
@@ -348,7 +381,8 @@ where
                         self.get_name()
                     );
                     let _ = socket_stream.close(None).await; // Attempt to close gracefully
-                    self.set_status(LoginStatus::Offline);
+                    emit_bot_event("bot-disconnect").await;
+                    update_status(LoginStatus::Offline).await;
                     return;
                 }
 
@@ -361,6 +395,26 @@ where
                             );
                             break;
                         }
+                        match self.receive(msg).await {
+                            Ok(Some(event)) => {
+                                let bot = Arc::clone(&bot);
+                                // 每个事件在独立任务中分发，避免耗时的指令阻塞消息接收
+                                tokio::spawn(async move {
+                                    let platform = bot.platform.clone();
+                                    if let Err(e) = bot.dispatch(event).await {
+                                        tracing::error!("Bot {} 分发事件失败: {}", platform, e);
+                                    }
+                                });
+                            }
+                            Ok(None) => {}
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Adapter {} failed to handle message: {}",
+                                    self.get_name(),
+                                    e
+                                );
+                            }
+                        }
                     }
                     Err(e) => {
                         tracing::error!(
@@ -372,13 +426,14 @@ where
                     }
                 }
             }
+            emit_bot_event("bot-disconnect").await;
 
             if !self.get_active() {
                 tracing::info!(
                     "Adapter {} became inactive after message loop. Not reconnecting.",
                     self.get_name()
                 );
-                self.set_status(LoginStatus::Offline);
+                update_status(LoginStatus::Offline).await;
                 return;
             }
 
@@ -394,7 +449,7 @@ where
                         self.get_name(),
                         ws_config.retry_times
                     );
-                    self.set_status(LoginStatus::Offline);
+                    update_status(LoginStatus::Offline).await;
                     return;
                 }
                 if retry_count == ws_config.retry_times {
@@ -410,7 +465,7 @@ where
             };
 
             retry_count += 1;
-            self.set_status(LoginStatus::Reconnect);
+            update_status(LoginStatus::Reconnect).await;
             tracing::info!(
                 "Adapter {} will retry connection in {}ms (attempt {}).",
                 self.get_name(),
@@ -1,12 +1,20 @@
 use crate::{
     adapter::Adapter,
     bot::Bot,
-    context::{Context, state::EventSystemSharedState},
+    command::{cancel, help},
+    config::BasicConfig,
+    context::{Context, listener::EmitMode, state::EventSystemSharedState},
     error::FrameworkResult,
+    event::{ErrorSource, InternalError},
     plugin::Plugin,
+    sync::panic_message,
 };
+use futures_util::FutureExt;
+use futures_util::future::join_all;
 use shirabe_utils::log;
+use std::panic::AssertUnwindSafe;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tokio::runtime::Runtime;
 
 // 事件应用主结构
@@ -19,12 +27,21 @@ pub struct App {
 
 impl App {
     pub fn new() -> Self {
-        App {
+        let app = App {
             config: config::Config::default(),
             adapters: Vec::new(),
             plugins: Vec::new(),
             shared_state: Arc::new(RwLock::new(EventSystemSharedState::default())),
+        };
+        // 内置的帮助指令
+        if let Err(e) = help::register(&app.context()) {
+            tracing::error!("注册内置 help 指令失败: {}", e);
         }
+        // 内置的取消指令
+        if let Err(e) = cancel::register(&app.context()) {
+            tracing::error!("注册内置 cancel 指令失败: {}", e);
+        }
+        app
     }
 
     /// 获取根事件上下文
@@ -45,6 +62,10 @@ impl App {
     }
 
     /// 启动应用
+    ///
+    /// 所有插件应用完成后在根上下文上发射 `ready` 事件。
+    /// 所有 Bot 任务结束或收到 Ctrl-C / SIGTERM 信号后停止全部 Bot，
+    /// 在应用关闭前发射 `dispose` 事件并调用各插件的 `on_unload`。
     pub fn run(&mut self) -> FrameworkResult<()> {
         log::init_logger();
         tracing::info!("启动应用……");
@@ -54,6 +75,15 @@ impl App {
         self.config = config::Config::builder()
             .add_source(config::File::new("config", config::FileFormat::Toml))
             .build()?;
+        match self.config.get::<BasicConfig>("basic") {
+            Ok(basic) => {
+                let ctx = self.context();
+                ctx.set_prefixes(&basic.prefix);
+                ctx.set_nicknames(&basic.nickname);
+                ctx.set_command_timeout(basic.command_timeout.map(Duration::from_secs));
+            }
+            Err(e) => tracing::warn!("读取 basic 配置失败，将使用默认的指令前缀: {}", e),
+        }
 
         // 获取应用根上下文
         let app_root_context = Arc::new(self.context());
@@ -64,13 +94,35 @@ impl App {
                 for plugin in &self.plugins {
                     let ctx_clone = Arc::clone(&app_root_context);
                     tracing::debug!("调用插件 '{}' 的 on_load 方法", plugin.name());
-                    if let Err(e) = plugin.on_load().await {
-                        tracing::error!("插件 '{}' 的 on_load 方法执行失败: {}", plugin.name(), e);
+                    // 插件的错误与 panic 只影响其自身，不会中断其余插件的加载
+                    let report = |message: String, panicked: bool| {
+                        app_root_context.report_error(
+                            None,
+                            InternalError {
+                                source: ErrorSource::Plugin {
+                                    name: plugin.name().to_string(),
+                                },
+                                message,
+                                panicked,
+                            },
+                        )
+                    };
+                    match AssertUnwindSafe(plugin.on_load()).catch_unwind().await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => report(format!("on_load 方法执行失败: {}", e), false),
+                        Err(payload) => report(
+                            format!("on_load 方法 panic: {}", panic_message(&*payload)),
+                            true,
+                        ),
                     }
-                    match plugin.apply(ctx_clone).await {
-                        Ok(_) => {}
-                        Err(e) => {
-                            tracing::error!("插件{}启动失败: {:?}", plugin.name(), e)
+                    match AssertUnwindSafe(plugin.apply(ctx_clone))
+                        .catch_unwind()
+                        .await
+                    {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => report(format!("启动失败: {}", e), false),
+                        Err(payload) => {
+                            report(format!("启动时 panic: {}", panic_message(&*payload)), true)
                         }
                     }
                 }
@@ -78,7 +130,13 @@ impl App {
             }
             // --- 插件加载结束 ---
 
+            // 所有插件均已应用，通知插件可以开始工作（如启动定时器、预热缓存）
+            app_root_context
+                .emit_async("ready", None, Vec::new(), EmitMode::Serial)
+                .await;
+
             // --- 从adapters中加载适配器并启动bots ---
+            let mut bots = vec![];
             let mut bot_handlers = vec![];
             if self.adapters.is_empty() {
                 tracing::warn!("没有加载任何适配器。应用将不会启动任何 Bot 或处理事件。");
@@ -91,14 +149,11 @@ impl App {
                 // 创建 Bot 实例
                 let bot = Arc::new(Bot::new(bot_context, current_adapter_instance));
 
-                // 将 Bot 实例注册到应用上下文中
-                // 这样插件或其他服务可以通过应用上下文访问到所有 Bot 实例
-                {
-                    let bots_arc_mutex = Arc::clone(&app_root_context.bots);
-                    let mut bots_guard = bots_arc_mutex.lock().unwrap();
-                    bots_guard.push(Arc::clone(&bot));
-                }
+                // 将 Bot 实例注册到共享状态中
+                // 这样插件或其他服务可以通过任意上下文访问到所有 Bot 实例
+                app_root_context.add_bot(Arc::clone(&bot)).await;
 
+                bots.push(Arc::clone(&bot));
                 let bot_for_task = Arc::clone(&bot);
                 let platform = bot_for_task.platform.clone();
                 tracing::info!(
@@ -118,20 +173,62 @@ impl App {
                 bot_handlers.push(handle);
             }
 
-            // 等待所有 Bot 任务完成
+            // 等待所有 Bot 任务完成，收到关闭信号时提前停止
             if !bot_handlers.is_empty() {
                 tracing::info!("所有 Bot 任务已派生，应用正在运行。等待 Bot 任务完成...");
-                for handle in bot_handlers {
-                    if let Err(e) = handle.await {
-                        tracing::error!("一个 Bot 任务执行时发生错误: {:?}", e);
+                tokio::select! {
+                    results = join_all(bot_handlers.iter_mut()) => {
+                        for e in results.into_iter().filter_map(Result::err) {
+                            tracing::error!("一个 Bot 任务执行时发生错误: {:?}", e);
+                        }
+                        tracing::info!("所有 Bot 任务已完成。");
+                    }
+                    _ = shutdown_signal() => {
+                        tracing::info!("收到关闭信号，正在停止所有 Bot...");
+                        for bot in bots {
+                            let platform = bot.platform.clone();
+                            if let Err(e) = bot.stop().await {
+                                tracing::error!("Bot {} 停止失败：{}", platform, e);
+                            }
+                        }
+                        for handle in &bot_handlers {
+                            handle.abort();
+                        }
                     }
                 }
-                tracing::info!("所有 Bot 任务已完成。");
             } else if self.adapters.is_empty() {
                 tracing::info!("由于没有加载适配器，所以没有 Bot 启动。");
             } else {
                 tracing::info!("适配器已加载，但没有 Bot 任务被创建或成功启动。");
             }
+
+            // 应用关闭前通知插件释放资源
+            app_root_context
+                .emit_async("dispose", None, Vec::new(), EmitMode::Serial)
+                .await;
+            for plugin in &self.plugins {
+                // 与加载时相同，一个插件的错误或 panic 不影响其余插件的卸载
+                let report = |message: String, panicked: bool| {
+                    app_root_context.report_error(
+                        None,
+                        InternalError {
+                            source: ErrorSource::Plugin {
+                                name: plugin.name().to_string(),
+                            },
+                            message,
+                            panicked,
+                        },
+                    )
+                };
+                match AssertUnwindSafe(plugin.on_unload()).catch_unwind().await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => report(format!("on_unload 方法执行失败: {}", e), false),
+                    Err(payload) => report(
+                        format!("on_unload 方法 panic: {}", panic_message(&*payload)),
+                        true,
+                    ),
+                }
+            }
         });
 
         tracing::info!("应用已关闭");
@@ -140,6 +237,35 @@ impl App {
     }
 }
 
+// 等待 Ctrl-C 或 SIGTERM 信号
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("监听 Ctrl-C 信号失败: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("监听 SIGTERM 信号失败: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
 impl Default for App {
     fn default() -> Self {
         Self::new()
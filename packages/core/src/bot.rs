@@ -1,9 +1,19 @@
 use crate::adapter::Adapter;
+use crate::command::schema::CommandSchema;
 use crate::context::Context;
-use crate::error::FrameworkResult;
+use crate::context::filter::ContextFilter;
+use crate::context::listener::EmitMode;
+use crate::context::middleware::{MiddlewareTerminal, Next};
+use crate::error::{FrameworkError, FrameworkResult};
+use crate::event::{ErrorSource, InternalError, LoginAdded, LoginUpdated, typed_event_args};
 use crate::message::MessageElement;
+use crate::session::{Session, SessionEvent};
+use crate::sync::{RwLockExt, panic_message};
 use crate::types::*;
-use std::sync::Arc;
+use futures_util::FutureExt;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 
 /// Bot 结构体，代表一个机器人实例
 pub struct Bot {
@@ -15,10 +25,12 @@ pub struct Bot {
     pub ctx: Arc<Context>,
     /// Bot的所在平台名称
     pub platform: String,
-    /// Bot在平台上的 ID
-    pub self_id: String,
-    /// Bot的登录状态
-    pub state: LoginStatus,
+    /// Bot在平台上的 ID，通过 `self_id`/`set_self_id` 读写
+    self_id: RwLock<String>,
+    /// Bot的登录状态，通过 `status`/`set_status` 读写
+    state: RwLock<LoginStatus>,
+    /// 是否已发射过 `login-added` 事件
+    login_added: AtomicBool,
     /// Bot的用户信息
     pub user: User,
 }
@@ -42,8 +54,9 @@ impl Bot {
             // config: Arc::new(config),
             ctx,
             platform,
-            self_id: String::new(),
-            state: LoginStatus::Offline,
+            self_id: RwLock::new(String::new()),
+            state: RwLock::new(LoginStatus::Offline),
+            login_added: AtomicBool::new(false),
             user: User::default(),
         }
     }
@@ -80,14 +93,197 @@ impl Bot {
         Ok(())
     }
 
+    /// 分发一个由适配器接收到的事件
+    ///
+    /// 根据事件构建 `Session`，在根上下文上发射同名事件（已知事件会携带对应的强类型事件参数）；
+    /// 若为 `message-created` 事件，则依次执行中间件，最后交由指令系统解析执行；
+    /// 若为 `interaction/command` 事件，则直接按其中的 `argv` 执行对应的指令。
+    ///
+    /// 中间件与指令返回的错误或发生的 panic 会通过 `internal/error` 事件上报，不会向上传播。
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - 适配器解析得到的会话事件
+    pub async fn dispatch(self: Arc<Self>, event: SessionEvent) -> FrameworkResult<()> {
+        // 适配器没有设置机器人账号时，以事件的接收者为准
+        if !event.self_id.is_empty() && self.self_id.read_recover().is_empty() {
+            self.set_self_id(&event.self_id);
+        }
+        let session = Arc::new(Session::new(Arc::clone(&self), event));
+        tracing::trace!("Bot {} 分发事件: {}", self.platform, session.type_);
+
+        self.ctx
+            .emit_async(
+                &session.type_,
+                Some(Arc::clone(&session)),
+                typed_event_args(&session.event),
+                EmitMode::Serial,
+            )
+            .await;
+
+        if session.type_ == "interaction/command" {
+            self.dispatch_argv(&session).await;
+            return Ok(());
+        }
+        if session.type_ != "message-created" {
+            return Ok(());
+        }
+
+        // 取出注册表快照、中间件列表与前缀，避免在 await 期间持有同步锁
+        let (registry, middlewares, prefixes) = {
+            let state = self.ctx.shared_state.read_recover();
+            (
+                Arc::clone(&state.command_registry.read_recover()),
+                state.middlewares.clone(),
+                state.prefix.prefixes_for(&session).to_vec(),
+            )
+        };
+
+        // 有中间件按角色过滤时，执行中间件链前先获取发送者的角色
+        if middlewares.iter().any(|m| m.filter.needs_roles()) {
+            session.resolve_roles().await;
+        }
+
+        // 指令执行作为中间件链的最后一环，指令的错误在此上报，不会传递给中间件
+        let ctx = Arc::clone(&self.ctx);
+        let terminal: MiddlewareTerminal = Box::new(move |session| {
+            Box::pin(async move {
+                // 部分适配器只提供消息的文本内容
+                let elements = if session.elements.is_empty() {
+                    vec![MessageElement::Text {
+                        text: session.content.clone(),
+                    }]
+                } else {
+                    session.elements.clone()
+                };
+                let prefixes: Vec<&str> = prefixes.iter().map(String::as_str).collect();
+                if let Err(e) = registry
+                    .parse_and_execute(Arc::clone(&session), &elements, &prefixes)
+                    .await
+                {
+                    ctx.report_error(
+                        Some(&session),
+                        InternalError {
+                            source: ErrorSource::Command,
+                            panicked: matches!(e, FrameworkError::Panic(_)),
+                            message: e.to_string(),
+                        },
+                    );
+                }
+                Ok(())
+            })
+        });
+        let chain = Next::new(middlewares, Arc::clone(&session), Some(terminal)).run();
+        let error = match AssertUnwindSafe(chain).catch_unwind().await {
+            Ok(Ok(())) => return Ok(()),
+            Ok(Err(e)) => InternalError {
+                source: ErrorSource::Middleware,
+                message: e.to_string(),
+                panicked: false,
+            },
+            Err(payload) => InternalError {
+                source: ErrorSource::Middleware,
+                message: panic_message(&*payload),
+                panicked: true,
+            },
+        };
+        self.ctx.report_error(Some(&session), error);
+        Ok(())
+    }
+
+    // 平台原生指令已拆分好参数，不经过中间件与前缀解析
+    async fn dispatch_argv(&self, session: &Arc<Session>) {
+        let Some(argv) = &session.event.argv else {
+            tracing::warn!("interaction/command 事件缺少 argv");
+            return;
+        };
+        let registry = {
+            let state = self.ctx.shared_state.read_recover();
+            Arc::clone(&state.command_registry.read_recover())
+        };
+        if let Err(e) = registry.execute_argv(Arc::clone(session), argv).await {
+            self.ctx.report_error(
+                Some(session),
+                InternalError {
+                    source: ErrorSource::Command,
+                    panicked: matches!(e, FrameworkError::Panic(_)),
+                    message: e.to_string(),
+                },
+            );
+        }
+    }
+
+    /// 获取可以在此机器人所在平台使用的指令结构，供适配器注册平台原生指令。
+    ///
+    /// 上下文过滤器限定为其他平台的指令会被排除，同名的指令只保留一个。
+    pub fn command_schemas(&self) -> Vec<CommandSchema> {
+        let state = self.ctx.shared_state.read_recover();
+        let registry = state.command_registry.read_recover();
+        registry.schemas(&ContextFilter::new().platform(&self.platform))
+    }
+
+    /// 获取机器人在平台上的 ID，尚未得知时为空字符串
+    pub fn self_id(&self) -> String {
+        self.self_id.read_recover().clone()
+    }
+
+    /// 设置机器人在平台上的 ID
+    ///
+    /// 适配器应在登录成功、得知机器人账号后调用；未设置时 `dispatch` 会从收到的第一个事件中获取。
+    pub fn set_self_id(&self, self_id: &str) {
+        *self.self_id.write_recover() = self_id.to_string();
+    }
+
+    /// 获取当前的登录状态
+    pub fn status(&self) -> LoginStatus {
+        *self.state.read_recover()
+    }
+
+    /// 修改登录状态
+    ///
+    /// 状态发生变化时在上下文上发射登录事件：首次变化发射 `login-added`，
+    /// 之后的变化发射 `login-updated`，参数分别为 `LoginAdded` / `LoginUpdated`。
+    /// 事件以异步方式发射，同步与异步监听器都会被执行。
+    pub async fn set_status(&self, status: LoginStatus) {
+        {
+            let mut state = self.state.write_recover();
+            if *state == status {
+                return;
+            }
+            *state = status;
+        }
+        let login = self.to_login();
+        if self.login_added.swap(true, Ordering::SeqCst) {
+            self.ctx
+                .emit_event_async(None, LoginUpdated { login }, EmitMode::Serial)
+                .await;
+        } else {
+            self.ctx
+                .emit_event_async(None, LoginAdded { login }, EmitMode::Serial)
+                .await;
+        }
+    }
+
     /// 修改登录状态为在线
-    pub fn online(&mut self) {
-        self.state = LoginStatus::Online
+    pub async fn online(&self) {
+        self.set_status(LoginStatus::Online).await
     }
 
     /// 修改登录状态为离线
-    pub fn offline(&mut self) {
-        self.state = LoginStatus::Offline
+    pub async fn offline(&self) {
+        self.set_status(LoginStatus::Offline).await
+    }
+
+    /// 根据机器人当前的信息构建登录信息
+    pub fn to_login(&self) -> Login {
+        Login {
+            sn: 0,
+            platform: Some(self.platform.clone()),
+            user: Some(self.user.clone()),
+            status: self.status(),
+            adapter: self.adapter.get_name(),
+            features: Vec::new(),
+        }
     }
 
     /// 向特定消息添加某个特定表态
@@ -1,32 +1,96 @@
+pub mod args;
+pub mod cancel;
+pub mod execution;
+pub mod help;
+pub mod permission;
+pub mod prefix;
+pub mod schema;
+pub mod shortcut;
+pub mod suggest;
+pub mod tokenizer;
+pub mod usage;
+
 use crate::context::filter::ContextFilter;
-use crate::error::FrameworkResult;
+use crate::context::listener::EmitMode;
+use crate::error::{FrameworkError, FrameworkResult};
+use crate::event::{CommandAfterExecute, CommandBeforeExecute};
+use crate::message::{IntoMessage, MessageElement};
 use crate::session::Session;
+use crate::sync::{RwLockExt, panic_message};
+use crate::types::Argv;
+use futures_util::FutureExt;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
 use std::pin::Pin;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use self::args::CommandArgs;
+use self::execution::CancellationToken;
+use self::permission::Permissions;
+use self::schema::{ArgValue, ArgumentDef, ArgumentSchema, CommandSchema, OptionDef, OptionSchema};
+use self::shortcut::{Shortcut, ShortcutPattern};
+use self::suggest::{SuggestionConfig, SuggestionMode};
+use self::tokenizer::{Token, Tokens};
+use self::usage::{Cooldown, UsageLimit, UsageScope};
+
+/// 未配置前缀时使用的默认指令前缀。
+pub const DEFAULT_PREFIXES: &[&str] = &["/"];
 
 /// 解析后的命令参数和选项。
 #[derive(Debug, Clone, Default)]
 pub struct ParsedArgs {
-    /// 位置参数。
-    pub arguments: Vec<String>,
+    /// 实际提供的位置参数，已按声明的类型解析。
+    pub arguments: Vec<ArgValue>,
     /// 提供的选项，例如 --option value。
-    /// 对于标志（没有值的选项），其值可能为空字符串或占位符。
-    pub options: HashMap<String, String>, // 选项名称 -> 选项值
+    /// 对于标志（没有值的选项），其值为 `ArgValue::Boolean(true)`。
+    pub options: HashMap<String, ArgValue>, // 选项名称 -> 选项值
+    /// 已声明的位置参数，包括使用默认值的参数。
+    pub named: HashMap<String, ArgValue>, // 参数名称 -> 参数值
+    /// 指令的取消令牌，指令超时或被 `cancel` 指令取消时触发。
+    /// 长时间运行的动作可以通过它提前结束并清理资源。
+    pub cancellation: CancellationToken,
+}
+
+// 取消令牌不参与比较
+impl PartialEq for ParsedArgs {
+    fn eq(&self, other: &Self) -> bool {
+        self.arguments == other.arguments
+            && self.options == other.options
+            && self.named == other.named
+    }
+}
+
+impl ParsedArgs {
+    /// 按名称获取已声明的位置参数
+    pub fn get(&self, name: &str) -> Option<&ArgValue> {
+        self.named.get(name)
+    }
+
+    /// 按名称获取选项
+    pub fn option(&self, name: &str) -> Option<&ArgValue> {
+        self.options.get(name)
+    }
 }
 
 /// 命令执行的异步动作的类型别名。
+///
+/// 返回的消息会由框架自动发送到会话所在的频道，`None` 表示不发送。
 pub type CommandAction = Box<
     dyn Fn(
             Arc<Session>,
             ParsedArgs,
-        ) -> Pin<Box<dyn Future<Output = FrameworkResult<()>> + Send + Sync>>
+        )
+            -> Pin<Box<dyn Future<Output = FrameworkResult<Option<Vec<MessageElement>>>> + Send>>
         + Send
         + Sync,
 >;
 
+/// 指令执行前的检查，返回 `Some(message)` 时中止执行并将 `message` 回复给用户。
+pub type CommandCheck = Box<dyn Fn(&Session, &ParsedArgs) -> Option<String> + Send + Sync>;
+
 /// 代表一个机器人指令。
 pub struct Command {
     /// 指令的主要名称。
@@ -39,9 +103,89 @@ pub struct Command {
     pub filter: ContextFilter,
     /// 指令执行时的异步动作。
     pub action: CommandAction,
-    // TODO: 为帮助信息生成和验证添加参数和选项定义的字段
-    // pub arg_defs: Vec<CommandArgumentDef>,
-    // pub opt_defs: Vec<CommandOptionDef>,
+    /// 位置参数的声明。
+    pub arguments: Vec<ArgumentDef>,
+    /// 选项的声明。
+    pub options: Vec<OptionDef>,
+    /// 使用示例，显示在帮助信息中。
+    pub examples: Vec<String>,
+    /// 调用指令所需的最低权限等级，为 0 时不作限制。
+    pub authority: u32,
+    /// 调用指令所需的权限名称。
+    pub permissions: Vec<String>,
+    /// 权限不足时回复的消息，未设置时使用全局配置。
+    pub denial_message: Option<String>,
+    /// 两次调用之间的最短间隔。
+    pub cooldown: Option<Cooldown>,
+    /// 一段时间内的最大调用次数，需要同时满足全部限制。
+    pub usage_limits: Vec<UsageLimit>,
+    /// 参数解析后按注册顺序执行的检查。
+    pub checks: Vec<CommandCheck>,
+    /// 无需前缀即可触发指令的快捷方式。
+    pub shortcuts: Vec<Shortcut>,
+    /// 执行时限，未设置时使用全局配置。
+    pub timeout: Option<Duration>,
+    /// 是否允许被冲突的指令替换，内置指令以此让插件定义同名指令。
+    pub overridable: bool,
+}
+
+impl Command {
+    /// 创建一个只有名称的指令，其余字段取默认值，动作不回复任何消息。
+    ///
+    /// 通常应使用 `Context::command` 构建指令，此方法便于直接构造 `Command` 并配合结构体更新语法设置其他字段。
+    pub fn new(name: impl Into<String>) -> Self {
+        Command {
+            name: name.into(),
+            aliases: Vec::new(),
+            description: None,
+            filter: ContextFilter::new(),
+            action: Box::new(|_session, _args| Box::pin(async { Ok(None) })),
+            arguments: Vec::new(),
+            options: Vec::new(),
+            examples: Vec::new(),
+            authority: 0,
+            permissions: Vec::new(),
+            denial_message: None,
+            cooldown: None,
+            usage_limits: Vec::new(),
+            checks: Vec::new(),
+            shortcuts: Vec::new(),
+            timeout: None,
+            overridable: false,
+        }
+    }
+
+    /// 指令的身份标识，用于区分不同上下文中注册的同名指令，例如冷却与调用次数的记录。
+    ///
+    /// 根上下文中的指令直接使用名称，其他指令附加上下文过滤器的 [`ContextFilter::fingerprint`]。
+    pub fn identity(&self) -> String {
+        let fingerprint = self.filter.fingerprint();
+        if fingerprint.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}@{}", self.name, fingerprint)
+        }
+    }
+
+    /// 指令的用法说明，例如 `roll <count:number> [sides:number]`。
+    pub fn usage(&self) -> String {
+        schema::usage(&self.name, &self.arguments)
+    }
+
+    /// 指令的结构描述，供适配器注册平台原生指令。
+    pub fn schema(&self) -> CommandSchema {
+        CommandSchema {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            arguments: self.arguments.iter().map(ArgumentSchema::from).collect(),
+            options: self.options.iter().map(OptionSchema::from).collect(),
+        }
+    }
+
+    /// 父指令的名称。子指令以 `.` 分隔路径命名，例如 `admin.ban` 的父指令为 `admin`。
+    pub fn parent(&self) -> Option<&str> {
+        self.name.rsplit_once('.').map(|(parent, _)| parent)
+    }
 }
 
 impl Debug for Command {
@@ -51,16 +195,34 @@ impl Debug for Command {
             .field("aliases", &self.aliases)
             .field("description", &self.description)
             .field("filter", &self.filter)
+            .field("arguments", &self.arguments)
+            .field("options", &self.options)
+            .field("examples", &self.examples)
+            .field("authority", &self.authority)
+            .field("permissions", &self.permissions)
+            .field("denial_message", &self.denial_message)
+            .field("cooldown", &self.cooldown)
+            .field("usage_limits", &self.usage_limits)
+            .field("checks", &format!("[{} 个检查]", self.checks.len()))
+            .field("shortcuts", &self.shortcuts)
+            .field("timeout", &self.timeout)
             .field("action", &"Box<dyn Fn(...)>") // 不打印闭包本身
             .finish()
     }
 }
 
+/// 共享的指令注册表。
+///
+/// 读取时克隆其中的 `Arc` 得到快照，无需在执行指令期间持有锁；注册与移除指令时写时复制，
+/// 已取出的快照不受影响。
+pub type SharedCommandRegistry = Arc<RwLock<Arc<CommandRegistry>>>;
+
 /// 管理并执行指令。
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct CommandRegistry {
-    /// 存储指令，将指令名称/别名映射到指令定义。
-    pub commands: HashMap<String, Arc<Command>>,
+    /// 存储指令，将指令名称/别名映射到按注册顺序排列的同名指令。
+    /// 同名的指令必须注册在互不相交的上下文中（见 [`ContextFilter::is_disjoint`]）。
+    pub commands: HashMap<String, Vec<Arc<Command>>>,
 }
 
 impl CommandRegistry {
@@ -70,29 +232,235 @@ impl CommandRegistry {
 
     /// 注册一个指令。
     /// 指令的主要名称及其所有别名都将被注册。
-    pub fn register(&mut self, command: Command) -> FrameworkResult<()> {
+    ///
+    /// 名称或别名可以与其他上下文中的指令相同，但若两者的上下文过滤器可能同时匹配
+    /// 同一会话，则返回 `FrameworkError::Command`，且不会注册任何名称。
+    /// 冲突的已有指令允许替换（[`Command::overridable`]）时不会报错：新指令的上下文完全覆盖
+    /// 原指令时移除原指令，否则两者并存，在新指令不匹配的会话中仍使用原指令。
+    ///
+    /// # Returns
+    /// 注册后的指令，可以传给 [`CommandRegistry::unregister`] 将其移除
+    pub fn register(&mut self, command: Command) -> FrameworkResult<Arc<Command>> {
         let command_arc = Arc::new(command);
-        // 注册主要名称
-        if self.commands.contains_key(&command_arc.name) {
-            // 可以返回错误或记录警告
-            tracing::warn!("指令 {} 已注册，将被覆盖。", command_arc.name);
+        let keys = || std::iter::once(&command_arc.name).chain(&command_arc.aliases);
+        let mut replaced: Vec<Arc<Command>> = Vec::new();
+        for key in keys() {
+            let conflicts = self
+                .commands
+                .get(key)
+                .into_iter()
+                .flatten()
+                .filter(|existing| !existing.filter.is_disjoint(&command_arc.filter));
+            for existing in conflicts {
+                if !existing.overridable {
+                    return Err(FrameworkError::Command(format!(
+                        "指令 '{}' 的名称或别名 '{}' 与已注册的指令 '{}' 冲突",
+                        command_arc.name, key, existing.name
+                    )));
+                }
+                // 只在新指令的上下文完全覆盖原指令时移除原指令，否则原指令继续作为后备
+                if command_arc.filter.covers(&existing.filter)
+                    && !replaced.iter().any(|c| Arc::ptr_eq(c, existing))
+                {
+                    replaced.push(Arc::clone(existing));
+                }
+            }
+        }
+        for existing in &replaced {
+            self.unregister(existing);
+            tracing::info!(
+                "指令 '{}' 已被同名指令 '{}' 替换",
+                existing.name,
+                command_arc.name
+            );
+        }
+        for key in keys() {
+            let candidates = self.commands.entry(key.clone()).or_default();
+            // 别名与名称重复时只记录一次
+            if !candidates.iter().any(|c| Arc::ptr_eq(c, &command_arc)) {
+                candidates.push(Arc::clone(&command_arc));
+            }
+        }
+        Ok(command_arc)
+    }
+
+    /// 移除一个指令及其全部别名，其他上下文中的同名指令不受影响。
+    ///
+    /// # Returns
+    /// 指令是否已注册
+    pub fn unregister(&mut self, command: &Arc<Command>) -> bool {
+        let mut removed = false;
+        for key in std::iter::once(&command.name).chain(&command.aliases) {
+            let Some(candidates) = self.commands.get_mut(key) else {
+                continue;
+            };
+            let count = candidates.len();
+            candidates.retain(|c| !Arc::ptr_eq(c, command));
+            removed |= candidates.len() != count;
+            if candidates.is_empty() {
+                self.commands.remove(key);
+            }
         }
+        removed
+    }
+
+    /// 按名称或别名获取指令。
+    ///
+    /// 只返回上下文过滤器匹配会话的指令；存在多个同名指令时返回匹配的那个，
+    /// 允许替换的指令（如内置指令）与其他指令同时匹配时优先返回其他指令，都不匹配时返回 `None`。
+    pub fn get(&self, name: &str, session: &Session) -> Option<Arc<Command>> {
         self.commands
-            .insert(command_arc.name.clone(), Arc::clone(&command_arc));
-
-        // 注册别名
-        for alias in &command_arc.aliases {
-            if self.commands.contains_key(alias) {
-                tracing::warn!(
-                    "指令 {} 的别名 {} 已注册或与另一指令冲突，将被覆盖。",
-                    command_arc.name,
-                    alias
-                );
+            .get(name)?
+            .iter()
+            .filter(|command| command.filter.matches_session(session))
+            .min_by_key(|command| command.overridable)
+            .cloned()
+    }
+
+    // 全部指令，每个指令只出现一次
+    fn all_commands(&self) -> impl Iterator<Item = &Arc<Command>> {
+        self.commands.iter().flat_map(|(key, candidates)| {
+            candidates
+                .iter()
+                .filter(move |command| *key == command.name)
+        })
+    }
+
+    /// 获取已注册的父指令（跳过未注册的中间路径），由近及远排列。
+    ///
+    /// 存在多个同名的父指令时，选择上下文可能与该指令重叠的那个。
+    pub fn ancestors(&self, command: &Command) -> Vec<Arc<Command>> {
+        let mut ancestors = Vec::new();
+        let mut path = command.parent();
+        while let Some(name) = path {
+            let parent = self.commands.get(name).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .find(|c| c.name == name && !c.filter.is_disjoint(&command.filter))
+            });
+            if let Some(parent) = parent {
+                ancestors.push(Arc::clone(parent));
+            }
+            path = name.rsplit_once('.').map(|(parent, _)| parent);
+        }
+        ancestors
+    }
+
+    /// 指令对给定会话是否可见：指令自身与所有父指令的过滤器都必须匹配。
+    pub fn is_visible(&self, command: &Command, session: &Session) -> bool {
+        command.filter.matches_session(session)
+            && self
+                .ancestors(command)
+                .iter()
+                .all(|parent| parent.filter.matches_session(session))
+    }
+
+    /// 检查权限是否允许调用指令：需要同时满足指令自身与所有父指令的要求。
+    pub fn is_permitted(&self, command: &Command, permissions: &Permissions) -> bool {
+        permissions.satisfies(command.authority, &command.permissions)
+            && self
+                .ancestors(command)
+                .iter()
+                .all(|parent| permissions.satisfies(parent.authority, &parent.permissions))
+    }
+
+    /// 指令或其父指令是否要求权限等级或权限名称。
+    pub fn requires_permission(&self, command: &Command) -> bool {
+        std::iter::once(command)
+            .chain(self.ancestors(command).iter().map(Arc::as_ref))
+            .any(|command| command.authority > 0 || !command.permissions.is_empty())
+    }
+
+    /// 获取对给定会话可见的全部指令（按名称排序，别名不会重复出现）。
+    pub fn visible_commands(&self, session: &Session) -> Vec<Arc<Command>> {
+        let mut commands: Vec<Arc<Command>> = self
+            .all_commands()
+            // 被同名指令取代的内置指令不再列出
+            .filter(|command| {
+                self.is_visible(command, session)
+                    && self
+                        .get(&command.name, session)
+                        .is_some_and(|preferred| Arc::ptr_eq(&preferred, command))
+            })
+            .cloned()
+            .collect();
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+        commands
+    }
+
+    /// 获取指令的直接子指令（按名称排序）。
+    ///
+    /// 若中间路径没有注册为指令，其下的子指令会归属于最近的已注册祖先。
+    /// 同名指令注册在多个上下文中时，返回的子指令可能属于不同的上下文。
+    pub fn children(&self, name: &str) -> Vec<Arc<Command>> {
+        let mut children: Vec<Arc<Command>> = self
+            .all_commands()
+            .filter(|command| {
+                self.ancestors(command)
+                    .first()
+                    .is_some_and(|parent| parent.name == name)
+            })
+            .cloned()
+            .collect();
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        children
+    }
+
+    /// 按最长路径解析指令。
+    ///
+    /// 每个词可以是指令名、别名或以 `.` 分隔的路径，之后的词依次尝试作为子指令，
+    /// 例如 `admin ban`、`admin.ban` 与 `adm.ban`（`adm` 为 `admin` 的别名）都会解析为 `admin.ban`。
+    /// 同名指令按 [`CommandRegistry::get`] 的规则选择。
+    ///
+    /// # Returns
+    /// 匹配到的指令及其占用的词数
+    pub fn resolve(&self, tokens: &[&str], session: &Session) -> Option<(Arc<Command>, usize)> {
+        // 把带 `.` 的词拆成路径段，并记录每段是否为所在词的最后一段
+        let segments = tokens.iter().enumerate().flat_map(|(i, token)| {
+            let count = token.split('.').count();
+            token
+                .split('.')
+                .enumerate()
+                .map(move |(j, segment)| (i, j + 1 == count, segment))
+        });
+        let mut best = None;
+        let mut path = String::new();
+        for (i, is_word_end, segment) in segments {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(segment);
+            if let Some(command) = self.get(&path, session) {
+                // 别名替换为指令的完整名称，以便继续匹配其子指令
+                path = command.name.clone();
+                // 只接受在词的边界处结束的匹配
+                if is_word_end {
+                    best = Some((command, i + 1));
+                }
+            } else {
+                // 中间路径可以没有注册为指令，只要还存在以它开头的指令就继续匹配
+                let prefix = format!("{}.", path);
+                if !self.commands.keys().any(|key| key.starts_with(&prefix)) {
+                    break;
+                }
             }
-            self.commands
-                .insert(alias.clone(), Arc::clone(&command_arc));
         }
-        Ok(())
+        best
+    }
+
+    /// 查找与消息文本匹配的快捷方式。
+    ///
+    /// 按名称顺序检查对会话可见的指令，返回第一个匹配的指令及其参数文本（见 [`Shortcut::matches`]）。
+    pub fn match_shortcut(&self, text: &str, session: &Session) -> Option<(Arc<Command>, String)> {
+        self.visible_commands(session)
+            .into_iter()
+            .find_map(|command| {
+                let args = command
+                    .shortcuts
+                    .iter()
+                    .find_map(|shortcut| shortcut.matches(text))?;
+                Some((command, args))
+            })
     }
 
     /// 解析消息并执行相应的指令（如果找到）。
@@ -100,125 +468,506 @@ impl CommandRegistry {
     /// # 参数
     ///
     /// * `session`: 当前会话，提供上下文和机器人访问。
-    /// * `message_content`: 消息的原始文本内容。
-    /// * `prefixes`: 命令前缀集合。
+    /// * `elements`: 消息的元素，按 [`tokenizer::tokenize`] 的规则分词。
+    /// * `prefixes`: 命令前缀集合，需出现在消息的第一个词的开头。
+    ///   `Bot::dispatch` 会传入会话所在群组的前缀（见 [`prefix::PrefixConfig::prefixes_for`]）。
+    ///
+    /// 消息以 @机器人 或配置的机器人昵称开头时，前缀可以省略。
+    /// 没有前缀或指令未知时，会依次尝试各指令的快捷方式（见 [`CommandBuilder::shortcut`]），
+    /// 之后才推测相近的指令。
     ///
     /// # 返回
     ///
-    /// * `Ok(true)` 如果找到了指令并尝试执行。
-    /// * `Ok(false)` 如果没有找到指令（例如，没有前缀匹配或未知指令）。
-    /// * `Err(FrameworkError)` 如果在指令执行期间发生错误。
+    /// * `Ok(true)` 如果找到了指令并尝试执行（参数校验失败时会回复用户，同样返回 `Ok(true)`），
+    ///   或者消息匹配了快捷方式，或者指令未知但回复了相近的指令名称。
+    /// * `Ok(false)` 如果没有找到指令（例如，没有前缀匹配且没有匹配的快捷方式，或未知指令且没有相近的指令）。
+    /// * `Err(FrameworkError)` 如果在指令执行期间发生错误（action panic 会转换为 `FrameworkError::Panic`）。
     pub async fn parse_and_execute(
         &self,
         session: Arc<Session>,
-        message_content: &str,
-        // TODO: 从配置中获取前缀
-        prefixes: &[&str], // 允许传入前缀
+        elements: &[MessageElement],
+        prefixes: &[&str],
     ) -> FrameworkResult<bool> {
-        let mut potential_command_text: Option<&str> = None;
+        // 确认模式下，用户发送句号以执行上一条消息推测的指令
+        let suggestion = session.app.shared_state.read_recover().suggestion.clone();
+        if let Some(tokens) = suggestion.take_confirmed(&session, elements) {
+            tracing::debug!("用户 {} 确认了推测的指令", session.user_id);
+            return self.execute_tokens(session, tokens, &suggestion).await;
+        }
+
+        let Tokens { source, mut tokens } = tokenizer::tokenize(elements);
+
+        // 以 @机器人 或机器人的昵称开头时，可以省略前缀
+        let nicknames = session
+            .app
+            .shared_state
+            .read_recover()
+            .prefix
+            .nicknames
+            .clone();
+        let mentioned = tokens.first().is_some_and(|token| {
+            matches!(&token.element, Some(MessageElement::At { id, .. }) if *id == session.self_id)
+        });
+        let nickname = tokens
+            .first()
+            .filter(|token| token.is_plain())
+            .and_then(|first| {
+                nicknames
+                    .iter()
+                    .find(|nickname| starts_with_nickname(&first.content, nickname))
+            });
+        let triggered = if mentioned {
+            tokens.remove(0);
+            true
+        } else if let Some(nickname) = nickname {
+            strip_first_token(&mut tokens, nickname.len(), true);
+            true
+        } else {
+            false
+        };
 
-        for prefix in prefixes {
-            if message_content.starts_with(prefix) {
-                potential_command_text =
-                    Some(message_content.trim_start_matches(prefix).trim_start());
-                break;
+        // 前缀只能出现在第一个普通文本词的开头
+        let prefix = tokens
+            .first()
+            .filter(|token| token.is_plain())
+            .and_then(|first| {
+                prefixes
+                    .iter()
+                    .find(|prefix| first.content.starts_with(**prefix))
+            });
+        match prefix {
+            Some(prefix) => strip_first_token(&mut tokens, prefix.len(), false),
+            None if triggered => {}
+            // 没有匹配的前缀，只可能是快捷方式
+            None => return self.execute_shortcut(session, source.trim()).await,
+        }
+        if tokens.is_empty() {
+            return Ok(false); // 只有前缀或昵称，没有指令名称
+        }
+        self.execute_tokens(session, Tokens { source, tokens }, &suggestion)
+            .await
+    }
+
+    /// 执行平台原生指令（`interaction/command` 事件）。
+    ///
+    /// `argv.name` 可以写作 `admin ban` 或 `admin.ban`，必须完整匹配一个指令，不使用前缀、
+    /// 快捷方式或未知指令推测；参数按 [`schema::parse_argv`] 解析，之后的检查与执行流程与消息指令相同。
+    ///
+    /// # 返回
+    ///
+    /// 与 [`CommandRegistry::parse_and_execute`] 相同，指令不存在或不可见时返回 `Ok(false)`。
+    pub async fn execute_argv(&self, session: Arc<Session>, argv: &Argv) -> FrameworkResult<bool> {
+        self.resolve_roles_for(&session, |_| true).await;
+        let parts: Vec<&str> = argv.name.split_whitespace().collect();
+        match self.resolve(&parts, &session) {
+            Some((command_arc, consumed)) if consumed == parts.len() => {
+                self.run_command(session, command_arc, RawArgs::Argv(argv))
+                    .await
+            }
+            _ => {
+                tracing::trace!("未知的原生指令: {}", argv.name);
+                Ok(false)
             }
         }
+    }
+
+    /// 导出可能在 `scope` 所描述的会话中使用的指令结构（按名称排序），供适配器注册平台原生指令。
+    ///
+    /// 指令或其父指令的上下文过滤器与 `scope` 不相交时被排除，注册在多个上下文中的同名指令只保留一个。
+    /// 传入 `ContextFilter::new()` 以导出全部指令。
+    pub fn schemas(&self, scope: &ContextFilter) -> Vec<CommandSchema> {
+        let mut commands: Vec<&Arc<Command>> = self
+            .all_commands()
+            .filter(|command| {
+                !command.filter.is_disjoint(scope)
+                    && self
+                        .ancestors(command)
+                        .iter()
+                        .all(|parent| !parent.filter.is_disjoint(scope))
+            })
+            .collect();
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+        commands.dedup_by(|a, b| a.name == b.name);
+        commands
+            .into_iter()
+            .map(|command| command.schema())
+            .collect()
+    }
+
+    // 解析去掉前缀后的各个词并执行指令，返回值与 parse_and_execute 相同
+    async fn execute_tokens(
+        &self,
+        session: Arc<Session>,
+        Tokens { source, tokens }: Tokens,
+        suggestion: &SuggestionConfig,
+    ) -> FrameworkResult<bool> {
+        self.resolve_roles_for(&session, |_| true).await;
+        // 指令名称由开头的文本词组成
+        let parts: Vec<&str> = tokens
+            .iter()
+            .map_while(|token| token.element.is_none().then_some(token.content.as_str()))
+            .collect();
 
-        if potential_command_text.is_none() {
-            return Ok(false); // 不是指令 (没有匹配的前缀)
+        if let Some((command_arc, consumed)) = self.resolve(&parts, &session) {
+            return self
+                .run_command(
+                    session,
+                    command_arc,
+                    RawArgs::Tokens(&tokens[consumed..], &source),
+                )
+                .await;
         }
 
-        let text = potential_command_text.unwrap();
-        if text.is_empty() {
-            return Ok(false); // 只有前缀，没有指令名称
+        tracing::trace!("未知指令: {}", tokens[0].content);
+        // 快捷方式优先于推测相近的指令
+        let text = &source[tokens[0].offset..];
+        if self.execute_shortcut(Arc::clone(&session), text).await? {
+            return Ok(true);
         }
 
-        let parts: Vec<&str> = text.split_whitespace().collect();
-        let command_name = parts[0];
-
-        if let Some(command_arc) = self.commands.get(command_name) {
-            // 检查指令自身注册时绑定的过滤器
-            if !command_arc.filter.matches_session(&session) {
-                tracing::trace!(
-                    "指令 {} 找到，但其上下文过滤器不匹配当前会话。",
-                    command_name
-                );
-                return Ok(false); // 指令的上下文过滤器不匹配
+        let Some(input) = parts
+            .first()
+            .filter(|_| suggestion.mode != SuggestionMode::Disabled)
+        else {
+            return Ok(false); // 未知指令
+        };
+        // 在当前会话可见的指令名称与别名中推测用户想要的指令
+        let candidates = suggest::suggest(
+            input,
+            self.commands
+                .iter()
+                .filter(|(_, candidates)| candidates.iter().any(|c| self.is_visible(c, &session)))
+                .map(|(key, _)| key.as_str()),
+        );
+        let quoted: Vec<String> = candidates
+            .iter()
+            .map(|name| format!("“{}”", name))
+            .collect();
+        let reply = match candidates.as_slice() {
+            [] => return Ok(false),
+            [name] if suggestion.mode == SuggestionMode::Confirm => {
+                let mut tokens = tokens.clone();
+                tokens[0].content = name.clone();
+                suggestion.remember(&session, Tokens { source, tokens });
+                format!("你要找的是不是{}？发送句号以执行推测的指令。", quoted[0])
             }
+            _ => format!("你要找的是不是{}？", quoted.join("或")),
+        };
+        session
+            .send(&[MessageElement::Text { text: reply }])
+            .await?;
+        Ok(true)
+    }
 
-            tracing::debug!("正在执行指令: {}", command_name);
-
-            let mut parsed_args = ParsedArgs::default();
-            let mut i = 1; // 从指令名称后的第一个部分开始
-            while i < parts.len() {
-                let part = parts[i];
-                if part.starts_with("--") {
-                    let option_name = part.trim_start_matches("--").to_string();
-                    if i + 1 < parts.len() {
-                        let next_part = parts[i + 1];
-                        // 值不能以 '-' 开头 (除非它是负数等特定情况，但这里简化)
-                        if !next_part.starts_with('-') && !next_part.is_empty() {
-                            // 主要修改在这里
-                            parsed_args
-                                .options
-                                .insert(option_name, next_part.to_string());
-                            i += 1; // 消耗选项值部分
-                        } else {
-                            // 下一个 token 是另一个选项，或没有值，当前长选项是标志
-                            parsed_args.options.insert(option_name, String::new());
-                        }
-                    } else {
-                        // 没有更多 token 了，当前长选项是标志
-                        parsed_args.options.insert(option_name, String::new());
-                    }
-                } else if part.starts_with('-') && part.len() > 1 && !part.starts_with("--") {
-                    // 短选项逻辑
-                    for (idx, char_val) in part.char_indices() {
-                        if idx == 0 {
-                            continue;
-                        }
-                        parsed_args
-                            .options
-                            .insert(char_val.to_string(), String::new());
-                    }
-                } else {
-                    // 参数
-                    parsed_args.arguments.push(part.to_string());
-                }
-                i += 1;
+    // 用快捷方式执行指令，没有匹配的快捷方式时返回 Ok(false)
+    async fn execute_shortcut(&self, session: Arc<Session>, text: &str) -> FrameworkResult<bool> {
+        self.resolve_roles_for(&session, |command| !command.shortcuts.is_empty())
+            .await;
+        let Some((command_arc, args)) = self.match_shortcut(text, &session) else {
+            return Ok(false);
+        };
+        tracing::debug!("消息匹配了指令 {} 的快捷方式", command_arc.name);
+        let Tokens { source, tokens } = tokenizer::tokenize(&[MessageElement::Text { text: args }]);
+        self.run_command(session, command_arc, RawArgs::Tokens(&tokens, &source))
+            .await
+    }
+
+    // 候选指令中有按角色过滤的指令时，查找与检查指令前先获取发送者的角色
+    async fn resolve_roles_for(&self, session: &Session, candidate: impl Fn(&Command) -> bool) {
+        if self
+            .all_commands()
+            .any(|command| candidate(command) && command.filter.needs_roles())
+        {
+            session.resolve_roles().await;
+        }
+    }
+
+    // 检查并执行已解析的指令
+    async fn run_command(
+        &self,
+        session: Arc<Session>,
+        command_arc: Arc<Command>,
+        args: RawArgs<'_>,
+    ) -> FrameworkResult<bool> {
+        let command_name = command_arc.name.as_str();
+        // 检查指令及其父指令注册时绑定的过滤器
+        if !self.is_visible(&command_arc, &session) {
+            tracing::trace!(
+                "指令 {} 找到，但其上下文过滤器不匹配当前会话。",
+                command_name
+            );
+            return Ok(false); // 指令的上下文过滤器不匹配
+        }
+
+        // 检查发送者的权限，权限不足时回复提示而不执行指令；
+        // 只有指令要求权限时才获取发送者的角色，供解析器使用
+        let config = session.app.shared_state.read_recover().permission.clone();
+        if self.requires_permission(&command_arc) {
+            session.resolve_roles().await;
+        }
+        let permissions = config.resolver.resolve(&session);
+        if !self.is_permitted(&command_arc, &permissions) {
+            tracing::debug!(
+                "用户 {} 的权限不足以调用指令 {}",
+                session.user_id,
+                command_name
+            );
+            let reply = command_arc
+                .denial_message
+                .clone()
+                .unwrap_or(config.denial_message);
+            session
+                .send(&[MessageElement::Text { text: reply }])
+                .await?;
+            return Ok(true);
+        }
+
+        tracing::debug!("正在执行指令: {}", command_name);
+
+        let parsed_args = match args {
+            RawArgs::Tokens(tokens, source) => {
+                schema::parse_args(tokens, source, &command_arc.arguments, &command_arc.options)
             }
+            RawArgs::Argv(argv) => {
+                schema::parse_argv(argv, &command_arc.arguments, &command_arc.options)
+            }
+        };
+        let parsed_args = match parsed_args {
+            Ok(parsed_args) => parsed_args,
+            Err(message) => {
+                // 参数校验失败，向用户说明原因与用法，不执行 action
+                tracing::debug!("指令 {} 参数校验失败: {}", command_name, message);
+                let reply = format!("{}\n用法：{}", message, command_arc.usage());
+                session
+                    .send(&[MessageElement::Text { text: reply }])
+                    .await?;
+                return Ok(true);
+            }
+        };
 
-            // 执行指令的动作
-            (command_arc.action)(session, parsed_args).await?;
-            Ok(true) // 指令找到并尝试执行
-        } else {
-            tracing::trace!("未知指令: {}", command_name);
-            Ok(false) // 未知指令
+        // 依次执行指令的检查，任一检查返回提示时中止
+        for check in &command_arc.checks {
+            let denial = panic::catch_unwind(AssertUnwindSafe(|| check(&session, &parsed_args)))
+                .map_err(|payload| {
+                    FrameworkError::Panic(format!(
+                        "指令 '{}' 的检查: {}",
+                        command_name,
+                        panic_message(&*payload)
+                    ))
+                })?;
+            if let Some(reply) = denial {
+                tracing::debug!("指令 {} 未通过检查: {}", command_name, reply);
+                session
+                    .send(&[MessageElement::Text { text: reply }])
+                    .await?;
+                return Ok(true);
+            }
+        }
+
+        // 先检查冷却与调用次数，执行前钩子允许执行后才记录本次调用
+        let usage = session.app.shared_state.read_recover().usage.clone();
+        if let Some(reply) = usage::check(&usage, &command_arc, &session).await? {
+            tracing::debug!("指令 {} 的调用受到限制: {}", command_name, reply);
+            session
+                .send(&[MessageElement::Text { text: reply }])
+                .await?;
+            return Ok(true);
+        }
+
+        // 全局的执行前钩子，可熔断监听器返回值时中止
+        let before = CommandBeforeExecute {
+            command: Arc::clone(&command_arc),
+            args: parsed_args.clone(),
+        };
+        if let Some(result) = session
+            .app
+            .emit_event_async(Some(Arc::clone(&session)), before, EmitMode::Serial)
+            .await
+        {
+            tracing::debug!("指令 {} 被执行前钩子中止", command_name);
+            if let Some(reply) = result.downcast_ref::<String>() {
+                let reply = reply.clone();
+                session
+                    .send(&[MessageElement::Text { text: reply }])
+                    .await?;
+            }
+            return Ok(true);
         }
+
+        // 参数有效且钩子允许执行时才计入冷却与调用次数；并发的调用可能已用完次数，因此再次检查
+        if let Some(reply) = usage::check_and_record(&usage, &command_arc, &session).await? {
+            tracing::debug!("指令 {} 的调用受到限制: {}", command_name, reply);
+            session
+                .send(&[MessageElement::Text { text: reply }])
+                .await?;
+            return Ok(true);
+        }
+
+        // 记录正在执行的指令，以便超时或通过 cancel 指令取消
+        let execution = session.app.shared_state.read_recover().execution.clone();
+        let timeout = command_arc.timeout.or(execution.timeout);
+        let cancellation = CancellationToken::new();
+        let mut parsed_args = parsed_args;
+        parsed_args.cancellation = cancellation.clone();
+        let guard = execution.track(&session, command_name, cancellation.clone());
+
+        // 执行指令的动作，捕获 action 中的 panic
+        let action = &command_arc.action;
+        let action_session = Arc::clone(&session);
+        let action_args = parsed_args.clone();
+        let run = async {
+            let result = AssertUnwindSafe(async move { action(action_session, action_args).await })
+                .catch_unwind()
+                .await
+                .map_err(|payload| {
+                    FrameworkError::Panic(format!(
+                        "指令 '{}': {}",
+                        command_name,
+                        panic_message(&*payload)
+                    ))
+                })
+                .and_then(|result| result);
+            // 自动发送 action 返回的消息
+            match result {
+                Ok(Some(reply)) => session.send(&reply).await.map(|_| ()),
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            }
+        };
+        let deadline = async {
+            match timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+        let (result, interruption) = tokio::select! {
+            result = run => (result, None),
+            _ = cancellation.cancelled() => {
+                tracing::debug!("指令 {} 已被取消", command_name);
+                (Ok(()), Some("指令已被取消".to_string()))
+            }
+            _ = deadline => {
+                tracing::debug!("指令 {} 执行超时", command_name);
+                cancellation.cancel();
+                let reply = execution.timeout_message.clone();
+                let result = session
+                    .send(&[MessageElement::Text { text: reply }])
+                    .await
+                    .map(|_| ());
+                (result, Some("指令执行超时".to_string()))
+            }
+        };
+        drop(guard);
+
+        let after = CommandAfterExecute {
+            command: Arc::clone(&command_arc),
+            args: parsed_args,
+            error: interruption.or_else(|| result.as_ref().err().map(ToString::to_string)),
+        };
+        session
+            .app
+            .emit_event_async(Some(Arc::clone(&session)), after, EmitMode::Serial)
+            .await;
+        result?;
+        Ok(true) // 指令找到并尝试执行
+    }
+}
+
+// 待解析的指令参数
+enum RawArgs<'a> {
+    // 消息中指令名称之后的各个词，以及消息的源文本
+    Tokens(&'a [Token], &'a str),
+    // 平台原生指令已拆分好的参数
+    Argv(&'a Argv),
+}
+
+// 昵称与指令之间可以使用的分隔符
+const NICKNAME_SEPARATORS: [char; 4] = [',', '，', ':', '：'];
+
+// 词以昵称开头，且昵称之后没有内容或紧跟分隔符，避免 `bot` 匹配 `bottle`
+fn starts_with_nickname(content: &str, nickname: &str) -> bool {
+    !nickname.is_empty()
+        && content.strip_prefix(nickname).is_some_and(|rest| {
+            rest.is_empty()
+                || rest.starts_with(char::is_whitespace)
+                || rest.starts_with(NICKNAME_SEPARATORS)
+        })
+}
+
+// 去掉第一个词开头的 len 个字节（以及称呼后的逗号、冒号），词因此变为空时将其移除
+fn strip_first_token(tokens: &mut Vec<Token>, len: usize, trim_punctuation: bool) {
+    let Some(first) = tokens.first_mut() else {
+        return;
+    };
+    first.content.drain(..len);
+    first.offset += len;
+    if trim_punctuation {
+        let trimmed = first.content.trim_start_matches(NICKNAME_SEPARATORS);
+        let removed = first.content.len() - trimmed.len();
+        first.content.drain(..removed);
+        first.offset += removed;
+    }
+    if first.content.is_empty() {
+        tokens.remove(0);
     }
 }
 
 /// 用于链式构建和注册指令的构建器。
 pub struct CommandBuilder {
     name: String,
+    arguments: Vec<ArgumentDef>,
+    options: Vec<OptionDef>,
+    declaration_errors: Vec<String>, // 声明中的错误，在 register 时报告
+    examples: Vec<String>,
+    authority: u32,
+    permissions: Vec<String>,
+    denial_message: Option<String>,
+    cooldown: Option<Cooldown>,
+    usage_limits: Vec<UsageLimit>,
+    checks: Vec<CommandCheck>,
+    shortcuts: Vec<Shortcut>,
+    timeout: Option<Duration>,
+    overridable: bool,
     aliases: Vec<String>,
     description: Option<String>,
     filter: ContextFilter, // 从调用 command() 的上下文中捕获
     action: Option<CommandAction>,
-    registry: Arc<RwLock<CommandRegistry>>, // 指向共享的指令注册表
+    registry: SharedCommandRegistry, // 指向共享的指令注册表
 }
 
 impl CommandBuilder {
     /// 创建一个新的 CommandBuilder。
     /// 通常由 `Context::command()` 调用。
+    ///
+    /// `declaration` 为指令名称，后面可以跟随参数声明，例如 `roll <count:number> [sides:number]`。
     pub fn new(
-        name: String,
+        declaration: String,
         filter: ContextFilter,
-        registry: Arc<RwLock<CommandRegistry>>,
+        registry: SharedCommandRegistry,
     ) -> Self {
+        let mut declaration_errors = Vec::new();
+        let (name, arguments) = match schema::parse_declaration(&declaration) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                declaration_errors.push(e);
+                (declaration.trim().to_string(), Vec::new())
+            }
+        };
         CommandBuilder {
             name,
+            arguments,
+            options: Vec::new(),
+            declaration_errors,
+            examples: Vec::new(),
+            authority: 0,
+            permissions: Vec::new(),
+            denial_message: None,
+            cooldown: None,
+            usage_limits: Vec::new(),
+            checks: Vec::new(),
+            shortcuts: Vec::new(),
+            timeout: None,
+            overridable: false,
             aliases: Vec::new(),
             description: None,
             filter,
@@ -233,24 +982,186 @@ impl CommandBuilder {
         self
     }
 
+    /// 为指令声明一个选项。
+    ///
+    /// `syntax` 由别名、可选的值声明与描述组成，例如 `"-v"`、`"-t <times:integer=1> 重复次数"`。
+    pub fn option(mut self, name: &str, syntax: &str) -> Self {
+        match OptionDef::parse(name, syntax) {
+            Ok(option) => self.options.push(option),
+            Err(e) => self.declaration_errors.push(e),
+        }
+        self
+    }
+
+    /// 为指令添加一个使用示例。
+    pub fn example(mut self, example: &str) -> Self {
+        self.examples.push(example.to_string());
+        self
+    }
+
     /// 为指令设置描述。
     pub fn description(mut self, description: &str) -> Self {
         self.description = Some(description.to_string());
         self
     }
 
+    /// 设置调用指令所需的最低权限等级。
+    ///
+    /// 发送者的权限由 [`Context::set_permission_resolver`](crate::context::Context::set_permission_resolver)
+    /// 设置的解析器确定，子指令同样需要满足父指令的要求。
+    pub fn authority(mut self, level: u32) -> Self {
+        self.authority = level;
+        self
+    }
+
+    /// 要求调用者拥有指定名称的权限，可以多次调用。
+    pub fn permission(mut self, permission: &str) -> Self {
+        self.permissions.push(permission.to_string());
+        self
+    }
+
+    /// 设置权限不足时回复的消息。
+    pub fn denial_message(mut self, message: &str) -> Self {
+        self.denial_message = Some(message.to_string());
+        self
+    }
+
+    /// 设置两次调用之间的最短间隔，冷却中的调用会收到剩余等待时间的提示。
+    pub fn cooldown(mut self, duration: Duration, scope: UsageScope) -> Self {
+        self.cooldown = Some(Cooldown { duration, scope });
+        self
+    }
+
+    /// 限制每个 `per_period` 内最多调用 `max` 次，可以多次调用以叠加多个限制。
+    ///
+    /// 调用记录默认保存在内存中，可以通过 `Context::set_usage_store` 使用持久化存储。
+    pub fn max_usage(mut self, max: u32, per_period: Duration, scope: UsageScope) -> Self {
+        self.usage_limits.push(UsageLimit {
+            max,
+            period: per_period,
+            scope,
+        });
+        self
+    }
+
+    /// 添加执行前的检查，检查在参数解析之后、指令执行之前按添加顺序运行。
+    ///
+    /// 检查返回 `Some(message)` 时指令不会执行，`message` 会回复给用户。
+    pub fn check<F>(mut self, check: F) -> Self
+    where
+        F: Fn(&Session, &ParsedArgs) -> Option<String> + Send + Sync + 'static,
+    {
+        self.checks.push(Box::new(check));
+        self
+    }
+
+    /// 添加快捷方式，匹配的消息无需前缀即可触发指令，可以多次调用。
+    ///
+    /// `pattern` 为短语（`&str` 或 `String`）时要求消息与其完全相同，为 [`shortcut::Regex`] 时
+    /// 捕获组依次追加在 `preset_args` 之后作为参数。快捷方式同样受指令的上下文过滤器限制。
+    ///
+    /// ```rust,ignore
+    /// ctx.command("weather <city>")
+    ///     .shortcut(Regex::new(r"^what's the weather in (.+?)\??$").unwrap(), "")
+    ///     .action(|_session, args| async move { Ok(format!("{} 的天气：晴", args.get("city").unwrap())) })
+    ///     .register()?;
+    /// ```
+    pub fn shortcut(mut self, pattern: impl Into<ShortcutPattern>, preset_args: &str) -> Self {
+        self.shortcuts.push(Shortcut {
+            pattern: pattern.into(),
+            args: preset_args.to_string(),
+        });
+        self
+    }
+
+    /// 设置执行时限，覆盖 `Context::set_command_timeout` 设置的全局时限。
+    ///
+    /// 超时后指令的动作会被中止，取消令牌被触发，用户会收到超时提示。
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 允许之后注册的冲突指令替换此指令，而不是注册失败。
+    ///
+    /// 冲突指令只在其上下文匹配的会话中取代此指令；上下文完全覆盖此指令时此指令被移除，
+    /// 且不会在替换者移除后恢复。
+    pub fn overridable(mut self) -> Self {
+        self.overridable = true;
+        self
+    }
+
     /// 设置指令的执行动作。
-    pub fn action<F, Fut>(mut self, f: F) -> Self
+    ///
+    /// 动作可以返回任何实现了 [`IntoMessage`] 的值，例如 `()`、`String`、
+    /// `Vec<MessageElement>` 或它们的 `Option`，返回的消息会被自动发送。
+    pub fn action<F, Fut, R>(mut self, f: F) -> Self
     where
         F: Fn(Arc<Session>, ParsedArgs) -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = FrameworkResult<()>> + Send + Sync + 'static,
+        Fut: Future<Output = FrameworkResult<R>> + Send + 'static,
+        R: IntoMessage,
     {
-        self.action = Some(Box::new(move |session, args| Box::pin(f(session, args))));
+        self.action = Some(Box::new(move |session, args| {
+            let fut = f(session, args);
+            Box::pin(async move { fut.await.map(IntoMessage::into_message) })
+        }));
+        self
+    }
+
+    /// 以 [`CommandArgs`] 结构体声明参数与选项，并设置接收该结构体的执行动作。
+    ///
+    /// 结构体中的位置参数即为指令的参数，指令声明中不能再包含参数；结构体中的选项追加在
+    /// 已声明的选项之后。它们与其他方式声明的参数一样用于帮助信息与参数校验。
+    /// 无法从解析结果构建结构体时（例如缺少非 `Option` 类型的选项），回复原因与用法而不执行动作。
+    pub fn typed_action<T, F, Fut, R>(mut self, f: F) -> Self
+    where
+        T: CommandArgs + 'static,
+        F: Fn(Arc<Session>, T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = FrameworkResult<R>> + Send + 'static,
+        R: IntoMessage,
+    {
+        match T::arguments().and_then(|arguments| {
+            schema::validate_arguments(&arguments)?;
+            Ok(arguments)
+        }) {
+            Ok(arguments) if arguments.is_empty() => {}
+            Ok(_) if !self.arguments.is_empty() => self
+                .declaration_errors
+                .push("参数不能同时在指令声明与参数结构体中声明".to_string()),
+            Ok(arguments) => self.arguments = arguments,
+            Err(e) => self.declaration_errors.push(e),
+        }
+        match T::options() {
+            Ok(options) => self.options.extend(options),
+            Err(e) => self.declaration_errors.push(e),
+        }
+        // 字段类型与参数值不符时，与参数校验失败一样向用户说明原因与用法
+        let usage = schema::usage(&self.name, &self.arguments);
+        self.action = Some(Box::new(move |session, args| match T::from_args(&args) {
+            Ok(args) => {
+                let fut = f(session, args);
+                Box::pin(async move { fut.await.map(IntoMessage::into_message) })
+            }
+            Err(message) => {
+                let reply = format!("{}\n用法：{}", message, usage);
+                Box::pin(async move { Ok(Some(vec![MessageElement::Text { text: reply }])) })
+            }
+        }));
         self
     }
 
     /// 构建并注册指令。
-    pub fn register(self) -> FrameworkResult<()> {
+    ///
+    /// # Returns
+    /// 用于移除该指令的句柄；名称或别名与同一上下文中的指令冲突时返回错误
+    pub fn register(self) -> FrameworkResult<CommandHandle> {
+        if !self.declaration_errors.is_empty() {
+            return Err(FrameworkError::Command(format!(
+                "指令 '{}' 的声明无效: {}",
+                self.name,
+                self.declaration_errors.join("; ")
+            )));
+        }
         let action = self.action.ok_or_else(|| {
             crate::error::FrameworkError::Command(format!("指令 '{}' 没有定义 action", self.name))
         })?;
@@ -261,14 +1172,41 @@ impl CommandBuilder {
             description: self.description,
             filter: self.filter,
             action,
+            arguments: self.arguments,
+            options: self.options,
+            examples: self.examples,
+            authority: self.authority,
+            permissions: self.permissions,
+            denial_message: self.denial_message,
+            cooldown: self.cooldown,
+            usage_limits: self.usage_limits,
+            checks: self.checks,
+            shortcuts: self.shortcuts,
+            timeout: self.timeout,
+            overridable: self.overridable,
         };
 
-        let mut registry_guard = self.registry.write().map_err(|_| {
-            crate::error::FrameworkError::Internal("无法获取 CommandRegistry 的写锁".to_string())
-        })?;
-
-        registry_guard.register(command)?;
+        let command = Arc::make_mut(&mut *self.registry.write_recover()).register(command)?;
         tracing::info!("指令 '{}' 已注册", self.name);
-        Ok(())
+        Ok(CommandHandle {
+            command,
+            registry: self.registry,
+        })
+    }
+}
+
+// 用于移除指令的句柄
+pub struct CommandHandle {
+    pub command: Arc<Command>,
+    pub registry: SharedCommandRegistry,
+}
+
+impl CommandHandle {
+    pub fn dispose(self) {
+        // 调用此方法移除指令及其全部别名
+        let mut registry = self.registry.write_recover();
+        if Arc::make_mut(&mut *registry).unregister(&self.command) {
+            tracing::info!("指令 '{}' 已移除", self.command.name);
+        }
     }
 }
@@ -0,0 +1,196 @@
+pub use shirabe_macros::CommandArgs;
+
+use super::ParsedArgs;
+use super::schema::{self, ArgType, ArgValue, ArgumentDef, OptionDef};
+
+/// 以结构体声明指令的参数与选项，通常通过 `#[derive(CommandArgs)]` 实现。
+///
+/// ```rust,ignore
+/// #[derive(CommandArgs)]
+/// struct Roll {
+///     count: i64,
+///     #[arg(default = 6)]
+///     sides: i64,
+///     #[option(short = 'v', description = "显示每次的结果")]
+///     verbose: bool,
+/// }
+///
+/// ctx.command("roll")
+///     .typed_action(|_session, args: Roll| async move { Ok(format!("{}d{}", args.count, args.sides)) })
+///     .register()?;
+/// ```
+///
+/// 字段默认为位置参数，按声明顺序排列；带 `#[option]` 的字段为选项。两者都支持以下属性：
+///
+/// * `default = 值`：未提供时使用的默认值，带默认值的位置参数可以省略；
+/// * `ty = "类型名"`：覆盖由字段类型推断的参数类型，例如 `"text"`、`"user"`；
+/// * `description = "..."`：选项的描述，显示在帮助信息中（仅 `#[option]`）；
+/// * `short = 'v'`：选项的短别名（仅 `#[option]`）。
+///
+/// `Option<T>` 类型的字段可以省略，`bool` 类型的选项为不带值的标志。
+///
+/// 带 `#[cancellation]` 的 [`CancellationToken`](super::execution::CancellationToken) 字段接收指令的取消令牌
+/// （[`ParsedArgs::cancellation`]），不作为参数或选项。
+pub trait CommandArgs: Sized {
+    /// 位置参数的声明，按顺序排列；类型名或默认值无效时返回错误
+    fn arguments() -> Result<Vec<ArgumentDef>, String>;
+    /// 选项的声明；类型名或默认值无效时返回错误
+    fn options() -> Result<Vec<OptionDef>, String>;
+    /// 从解析结果构建，字段类型与参数值不符时返回面向用户的错误信息
+    fn from_args(args: &ParsedArgs) -> Result<Self, String>;
+}
+
+/// 可以作为 [`CommandArgs`] 字段的类型
+pub trait ArgField: Sized {
+    /// 字段对应的参数类型
+    const TYPE: ArgType;
+    /// 字段是否可以省略
+    const OPTIONAL: bool = false;
+
+    /// 从参数值构建，`None` 表示参数未提供
+    fn from_value(value: Option<&ArgValue>) -> Option<Self>;
+}
+
+impl ArgField for String {
+    const TYPE: ArgType = ArgType::String;
+
+    fn from_value(value: Option<&ArgValue>) -> Option<Self> {
+        value?.as_str().map(str::to_string)
+    }
+}
+
+impl ArgField for i64 {
+    const TYPE: ArgType = ArgType::Integer;
+
+    fn from_value(value: Option<&ArgValue>) -> Option<Self> {
+        value?.as_i64()
+    }
+}
+
+impl ArgField for i32 {
+    const TYPE: ArgType = ArgType::Integer;
+
+    fn from_value(value: Option<&ArgValue>) -> Option<Self> {
+        value?.as_i64()?.try_into().ok()
+    }
+}
+
+impl ArgField for u32 {
+    const TYPE: ArgType = ArgType::Integer;
+
+    fn from_value(value: Option<&ArgValue>) -> Option<Self> {
+        value?.as_i64()?.try_into().ok()
+    }
+}
+
+impl ArgField for u64 {
+    const TYPE: ArgType = ArgType::Integer;
+
+    fn from_value(value: Option<&ArgValue>) -> Option<Self> {
+        value?.as_i64()?.try_into().ok()
+    }
+}
+
+impl ArgField for f64 {
+    const TYPE: ArgType = ArgType::Number;
+
+    fn from_value(value: Option<&ArgValue>) -> Option<Self> {
+        value?.as_f64()
+    }
+}
+
+impl ArgField for bool {
+    const TYPE: ArgType = ArgType::Boolean;
+
+    // 未出现的标志为 false
+    fn from_value(value: Option<&ArgValue>) -> Option<Self> {
+        value.map_or(Some(false), ArgValue::as_bool)
+    }
+}
+
+impl<T: ArgField> ArgField for Option<T> {
+    const TYPE: ArgType = T::TYPE;
+    const OPTIONAL: bool = true;
+
+    fn from_value(value: Option<&ArgValue>) -> Option<Self> {
+        match value {
+            Some(value) => T::from_value(Some(value)).map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+// 字段的参数类型，`ty` 覆盖由字段类型推断的类型
+fn field_type<T: ArgField>(ty: Option<&str>) -> Result<ArgType, String> {
+    match ty {
+        Some(ty) => ArgType::from_name(ty).ok_or_else(|| format!("未知的参数类型 {}", ty)),
+        None => Ok(T::TYPE),
+    }
+}
+
+/// 生成位置参数的声明，供 `#[derive(CommandArgs)]` 使用
+#[doc(hidden)]
+pub fn argument_def<T: ArgField>(
+    name: &str,
+    ty: Option<&str>,
+    default: Option<&str>,
+) -> Result<ArgumentDef, String> {
+    let ty = field_type::<T>(ty)?;
+    let default = default
+        .map(|raw| schema::parse_default(name, ty, raw))
+        .transpose()?;
+    Ok(ArgumentDef {
+        name: name.to_string(),
+        ty,
+        required: default.is_none() && !T::OPTIONAL,
+        default,
+    })
+}
+
+/// 生成选项的声明，供 `#[derive(CommandArgs)]` 使用；`bool` 类型的选项为标志
+#[doc(hidden)]
+pub fn option_def<T: ArgField>(
+    name: &str,
+    short: Option<char>,
+    ty: Option<&str>,
+    default: Option<&str>,
+    description: Option<&str>,
+) -> Result<OptionDef, String> {
+    let mut aliases = vec![name.to_string()];
+    aliases.extend(short.map(String::from));
+    let is_flag = ty.is_none() && T::TYPE == ArgType::Boolean;
+    let ty = if is_flag {
+        None
+    } else {
+        Some(field_type::<T>(ty)?)
+    };
+    let default = match (ty, default) {
+        (Some(ty), Some(raw)) => Some(schema::parse_default(name, ty, raw)?),
+        _ => None,
+    };
+    Ok(OptionDef {
+        name: name.to_string(),
+        aliases,
+        ty,
+        default,
+        description: description.map(str::to_string),
+    })
+}
+
+/// 从参数值构建字段，供 `#[derive(CommandArgs)]` 使用
+#[doc(hidden)]
+pub fn field_value<T: ArgField>(name: &str, value: Option<&ArgValue>) -> Result<T, String> {
+    T::from_value(value).ok_or_else(|| match value {
+        Some(value) => format!("参数 {} 的值 {} 无效。", name, value),
+        None => format!("缺少参数 {}。", name),
+    })
+}
+
+/// 从选项值构建字段，供 `#[derive(CommandArgs)]` 使用
+#[doc(hidden)]
+pub fn option_value<T: ArgField>(name: &str, value: Option<&ArgValue>) -> Result<T, String> {
+    T::from_value(value).ok_or_else(|| match value {
+        Some(value) => format!("选项 {} 的值 {} 无效。", name, value),
+        None => format!("缺少选项 {}。", name),
+    })
+}
@@ -0,0 +1,29 @@
+use super::CommandHandle;
+use crate::context::Context;
+use crate::error::FrameworkResult;
+use crate::sync::RwLockExt;
+
+/// 取消指令的名称
+pub const CANCEL_COMMAND: &str = "cancel";
+
+/// 在给定上下文上注册内置的 `cancel` 指令。
+///
+/// `cancel` 取消发送者在当前频道中正在执行的全部指令，被取消的指令会立即停止执行，
+/// 其取消令牌（[`ParsedArgs::cancellation`](super::ParsedArgs::cancellation)）同时被触发。
+///
+/// 注册的指令允许替换，插件定义的同名指令在其上下文中取代内置指令。
+pub fn register(ctx: &Context) -> FrameworkResult<CommandHandle> {
+    ctx.command(CANCEL_COMMAND)
+        .description("取消自己在当前频道中正在执行的指令")
+        .overridable()
+        .action(|session, _args| async move {
+            let execution = session.app.shared_state.read_recover().execution.clone();
+            let cancelled = execution.cancel(&session);
+            Ok(if cancelled.is_empty() {
+                "当前没有正在执行的指令。".to_string()
+            } else {
+                format!("已取消指令：{}。", cancelled.join("，"))
+            })
+        })
+        .register()
+}
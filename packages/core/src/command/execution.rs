@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::cancel::CANCEL_COMMAND;
+use crate::session::Session;
+use crate::sync::MutexExt;
+
+/// 未配置时使用的超时提示
+pub const DEFAULT_TIMEOUT_MESSAGE: &str = "指令执行时间过长，已被中止。";
+
+// 正在执行的指令
+#[derive(Debug)]
+struct RunningCommand {
+    id: Uuid,
+    name: String,
+    token: CancellationToken,
+}
+
+/// 指令执行的配置，存储在共享状态中
+#[derive(Debug, Clone)]
+pub struct ExecutionConfig {
+    /// 指令没有单独设置时限时使用的执行时限，`None` 表示不限制
+    pub timeout: Option<Duration>,
+    /// 指令执行超时时回复的消息
+    pub timeout_message: String,
+    running: Arc<Mutex<HashMap<String, Vec<RunningCommand>>>>, // 平台:频道:用户 -> 正在执行的指令
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        ExecutionConfig {
+            timeout: None,
+            timeout_message: DEFAULT_TIMEOUT_MESSAGE.to_string(),
+            running: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl ExecutionConfig {
+    // 用户只能取消自己在同一频道中执行的指令
+    fn key(session: &Session) -> String {
+        format!(
+            "{}:{}:{}",
+            session.platform, session.channel_id, session.user_id
+        )
+    }
+
+    /// 记录开始执行的指令，返回的守卫被丢弃时移除记录
+    pub(crate) fn track(
+        &self,
+        session: &Session,
+        name: &str,
+        token: CancellationToken,
+    ) -> RunningGuard {
+        let key = Self::key(session);
+        let id = Uuid::new_v4();
+        self.running
+            .lock_recover()
+            .entry(key.clone())
+            .or_default()
+            .push(RunningCommand {
+                id,
+                name: name.to_string(),
+                token,
+            });
+        RunningGuard {
+            running: Arc::clone(&self.running),
+            key,
+            id,
+        }
+    }
+
+    /// 取消发送者在会话所在频道中正在执行的指令，取消指令本身除外。
+    ///
+    /// # Returns
+    /// 被取消的指令名称，按开始执行的顺序排列
+    pub fn cancel(&self, session: &Session) -> Vec<String> {
+        let running = self.running.lock_recover();
+        running
+            .get(&Self::key(session))
+            .into_iter()
+            .flatten()
+            .filter(|command| command.name != CANCEL_COMMAND && !command.token.is_cancelled())
+            .map(|command| {
+                command.token.cancel();
+                command.name.clone()
+            })
+            .collect()
+    }
+}
+
+// 指令执行结束时移除其记录
+pub(crate) struct RunningGuard {
+    running: Arc<Mutex<HashMap<String, Vec<RunningCommand>>>>,
+    key: String,
+    id: Uuid,
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        let mut running = self.running.lock_recover();
+        if let Some(commands) = running.get_mut(&self.key) {
+            commands.retain(|command| command.id != self.id);
+            if commands.is_empty() {
+                running.remove(&self.key);
+            }
+        }
+    }
+}
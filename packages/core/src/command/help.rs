@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use super::{Command, CommandHandle, CommandRegistry};
+use crate::context::Context;
+use crate::error::FrameworkResult;
+use crate::message::MessageElement;
+use crate::session::Session;
+use crate::sync::RwLockExt;
+
+/// 内置帮助指令的名称
+pub const HELP_COMMAND: &str = "help";
+
+/// 在给定上下文上注册内置的 `help` 指令。
+///
+/// `help` 以树形列出当前会话可见的全部指令，`help <指令名>` 显示指定指令的用法、
+/// 选项、别名、示例与子指令。指令名可以写作 `admin ban` 或 `admin.ban`。
+/// 指令是否可见由其自身及父指令注册时的上下文过滤器决定。
+///
+/// 注册的指令允许替换，插件定义的同名指令在其上下文中取代内置指令。
+pub fn register(ctx: &Context) -> FrameworkResult<CommandHandle> {
+    ctx.command("help [command:text]")
+        .description("显示指令列表或指定指令的帮助")
+        .overridable()
+        .example("help")
+        .example("help help")
+        .example("help admin ban")
+        .action(|session, args| async move {
+            let registry_arc =
+                Arc::clone(&session.app.shared_state.read_recover().command_registry);
+            let registry = Arc::clone(&registry_arc.read_recover());
+            Ok(match args.get("command").and_then(|v| v.as_str()) {
+                Some(name) => render_command_detail(&registry, &session, name),
+                None => render_command_list(&registry, &session),
+            })
+        })
+        .register()
+}
+
+/// 渲染当前会话可见的指令列表
+pub fn render_command_list(registry: &CommandRegistry, session: &Session) -> Vec<MessageElement> {
+    let commands = registry.visible_commands(session);
+    if commands.is_empty() {
+        return text_elements(vec!["当前没有可用的指令。".to_string()]);
+    }
+    let mut lines = vec!["当前可用的指令有：".to_string()];
+    // 父指令未注册或不可见的指令作为树的根
+    let roots = commands.iter().filter(|command| {
+        registry
+            .ancestors(command)
+            .first()
+            .is_none_or(|parent| !registry.is_visible(parent, session))
+    });
+    for command in roots {
+        push_command_tree(registry, session, command, 1, &mut lines);
+    }
+    lines.push(format!("输入“{} 指令名”查看特定指令的用法。", HELP_COMMAND));
+    text_elements(lines)
+}
+
+// 按层级缩进渲染指令及其可见的子指令
+fn push_command_tree(
+    registry: &CommandRegistry,
+    session: &Session,
+    command: &Command,
+    depth: usize,
+    lines: &mut Vec<String>,
+) {
+    lines.push(command_summary(command, depth));
+    for child in registry.children(&command.name) {
+        if registry.is_visible(&child, session) {
+            push_command_tree(registry, session, &child, depth + 1, lines);
+        }
+    }
+}
+
+fn command_summary(command: &Command, depth: usize) -> String {
+    let indent = "    ".repeat(depth);
+    match &command.description {
+        Some(description) => format!("{}{}  {}", indent, command.name, description),
+        None => format!("{}{}", indent, command.name),
+    }
+}
+
+/// 渲染指定指令的详细帮助，指令不存在或对当前会话不可见时给出提示
+pub fn render_command_detail(
+    registry: &CommandRegistry,
+    session: &Session,
+    name: &str,
+) -> Vec<MessageElement> {
+    let tokens: Vec<&str> = name.split_whitespace().collect();
+    match registry
+        .resolve(&tokens, session)
+        .filter(|(command, consumed)| {
+            *consumed == tokens.len() && registry.is_visible(command, session)
+        }) {
+        Some((command, _)) => text_elements(command_detail_lines(registry, session, &command)),
+        None => text_elements(vec![format!("指令 {} 不存在。", name)]),
+    }
+}
+
+fn command_detail_lines(
+    registry: &CommandRegistry,
+    session: &Session,
+    command: &Command,
+) -> Vec<String> {
+    let mut lines = vec![command.usage()];
+    if let Some(description) = &command.description {
+        lines.push(description.clone());
+    }
+    if !command.aliases.is_empty() {
+        lines.push(format!("别名：{}", command.aliases.join("，")));
+    }
+    if !command.options.is_empty() {
+        lines.push("可用的选项有：".to_string());
+        for option in &command.options {
+            match &option.description {
+                Some(description) => lines.push(format!("    {}  {}", option, description)),
+                None => lines.push(format!("    {}", option)),
+            }
+        }
+    }
+    if !command.examples.is_empty() {
+        lines.push("使用示例：".to_string());
+        for example in &command.examples {
+            lines.push(format!("    {}", example));
+        }
+    }
+    let children: Vec<String> = registry
+        .children(&command.name)
+        .iter()
+        .filter(|child| registry.is_visible(child, session))
+        .map(|child| command_summary(child, 1))
+        .collect();
+    if !children.is_empty() {
+        lines.push("可用的子指令有：".to_string());
+        lines.extend(children);
+    }
+    lines
+}
+
+// 每行对应一个文本元素，行与行之间以换行分隔
+fn text_elements(lines: Vec<String>) -> Vec<MessageElement> {
+    let last = lines.len().saturating_sub(1);
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| MessageElement::Text {
+            text: if i < last {
+                format!("{}\n", line)
+            } else {
+                line
+            },
+        })
+        .collect()
+}
@@ -0,0 +1,143 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::session::Session;
+
+/// 未单独配置的用户的权限等级
+pub const DEFAULT_AUTHORITY: u32 = 1;
+
+/// 未配置时使用的权限不足提示
+pub const DEFAULT_DENIAL_MESSAGE: &str = "权限不足。";
+
+/// 用户拥有的权限
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Permissions {
+    /// 权限等级，不低于指令要求的等级时才能调用该指令
+    pub authority: u32,
+    /// 额外授予的权限名称，例如 `admin.ban`
+    pub permissions: HashSet<String>,
+}
+
+impl Permissions {
+    /// 创建只有权限等级的权限
+    pub fn with_authority(authority: u32) -> Self {
+        Permissions {
+            authority,
+            permissions: HashSet::new(),
+        }
+    }
+
+    /// 追加一项权限名称
+    pub fn grant(mut self, permission: &str) -> Self {
+        self.permissions.insert(permission.to_string());
+        self
+    }
+
+    /// 合并另一份权限：等级取较高者，权限名称取并集
+    pub fn merge(&mut self, other: &Permissions) {
+        self.authority = self.authority.max(other.authority);
+        self.permissions.extend(other.permissions.iter().cloned());
+    }
+
+    /// 是否满足给定的权限等级与全部权限名称
+    pub fn satisfies(&self, authority: u32, permissions: &[String]) -> bool {
+        self.authority >= authority && permissions.iter().all(|p| self.permissions.contains(p))
+    }
+}
+
+/// 根据会话确定发送者的权限
+pub trait PermissionResolver: Send + Sync {
+    fn resolve(&self, session: &Session) -> Permissions;
+}
+
+impl<F> PermissionResolver for F
+where
+    F: Fn(&Session) -> Permissions + Send + Sync,
+{
+    fn resolve(&self, session: &Session) -> Permissions {
+        self(session)
+    }
+}
+
+/// 默认的权限解析器，按平台用户与群组角色分配权限。
+///
+/// 用户的权限为默认等级、该用户自身以及其全部角色所配置权限的合并。
+#[derive(Debug, Clone)]
+pub struct DefaultPermissionResolver {
+    /// 所有用户都具有的权限等级
+    pub default_authority: u32,
+    users: HashMap<(String, String), Permissions>, // (平台, 用户 ID) -> 权限
+    roles: HashMap<String, Permissions>,           // 角色 ID -> 权限
+}
+
+impl Default for DefaultPermissionResolver {
+    fn default() -> Self {
+        DefaultPermissionResolver {
+            default_authority: DEFAULT_AUTHORITY,
+            users: HashMap::new(),
+            roles: HashMap::new(),
+        }
+    }
+}
+
+impl DefaultPermissionResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为指定平台上的用户配置权限
+    pub fn user(mut self, platform: &str, user_id: &str, permissions: Permissions) -> Self {
+        self.users
+            .insert((platform.to_string(), user_id.to_string()), permissions);
+        self
+    }
+
+    /// 为拥有指定角色的成员配置权限
+    pub fn role(mut self, role_id: &str, permissions: Permissions) -> Self {
+        self.roles.insert(role_id.to_string(), permissions);
+        self
+    }
+}
+
+impl PermissionResolver for DefaultPermissionResolver {
+    fn resolve(&self, session: &Session) -> Permissions {
+        let mut resolved = Permissions::with_authority(self.default_authority);
+        let key = (session.platform.clone(), session.user_id.clone());
+        if let Some(permissions) = self.users.get(&key) {
+            resolved.merge(permissions);
+        }
+        for role in session.roles().iter().flatten() {
+            if let Some(permissions) = self.roles.get(role) {
+                resolved.merge(permissions);
+            }
+        }
+        resolved
+    }
+}
+
+/// 指令权限检查的配置，存储在共享状态中
+#[derive(Clone)]
+pub struct PermissionConfig {
+    pub resolver: Arc<dyn PermissionResolver>,
+    /// 指令没有单独设置提示时，权限不足所回复的消息
+    pub denial_message: String,
+}
+
+impl Default for PermissionConfig {
+    fn default() -> Self {
+        PermissionConfig {
+            resolver: Arc::new(DefaultPermissionResolver::default()),
+            denial_message: DEFAULT_DENIAL_MESSAGE.to_string(),
+        }
+    }
+}
+
+impl fmt::Debug for PermissionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PermissionConfig")
+            .field("resolver", &"Arc<dyn PermissionResolver>")
+            .field("denial_message", &self.denial_message)
+            .finish()
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use super::DEFAULT_PREFIXES;
+use crate::session::Session;
+
+/// 指令的触发方式，存储在共享状态中
+#[derive(Debug, Clone)]
+pub struct PrefixConfig {
+    /// 全局的指令前缀，来自配置文件的 `basic.prefix`
+    pub prefixes: Vec<String>,
+    /// 机器人的昵称，以昵称开头的消息无需前缀即可触发指令，来自配置文件的 `basic.nickname`
+    pub nicknames: Vec<String>,
+    guild_prefixes: HashMap<(String, String), Vec<String>>, // (平台, 群组 ID) -> 前缀
+}
+
+impl Default for PrefixConfig {
+    fn default() -> Self {
+        PrefixConfig {
+            prefixes: DEFAULT_PREFIXES.iter().map(|p| p.to_string()).collect(),
+            nicknames: Vec::new(),
+            guild_prefixes: HashMap::new(),
+        }
+    }
+}
+
+impl PrefixConfig {
+    /// 会话所在群组使用的前缀，群组没有单独设置时为全局前缀
+    pub fn prefixes_for(&self, session: &Session) -> &[String] {
+        self.guild_prefixes(&session.platform, &session.guild_id)
+            .unwrap_or(&self.prefixes)
+    }
+
+    /// 群组单独设置的前缀
+    pub fn guild_prefixes(&self, platform: &str, guild_id: &str) -> Option<&[String]> {
+        self.guild_prefixes
+            .get(&(platform.to_string(), guild_id.to_string()))
+            .map(Vec::as_slice)
+    }
+
+    /// 为群组单独设置前缀，覆盖全局前缀
+    pub fn set_guild_prefixes(&mut self, platform: &str, guild_id: &str, prefixes: Vec<String>) {
+        self.guild_prefixes
+            .insert((platform.to_string(), guild_id.to_string()), prefixes);
+    }
+
+    /// 移除群组单独设置的前缀，恢复使用全局前缀
+    pub fn remove_guild_prefixes(&mut self, platform: &str, guild_id: &str) -> bool {
+        self.guild_prefixes
+            .remove(&(platform.to_string(), guild_id.to_string()))
+            .is_some()
+    }
+}
@@ -0,0 +1,735 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use super::ParsedArgs;
+use super::tokenizer::Token;
+use crate::message::MessageElement;
+use crate::types::Argv;
+
+/// 参数类型，序列化为声明中使用的类型名
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArgType {
+    /// 单个词
+    String,
+    /// 剩余的全部文本，只能用于最后一个参数
+    Text,
+    /// 数字
+    Number,
+    /// 整数
+    Integer,
+    /// 布尔值
+    Boolean,
+    /// 提及的用户
+    User,
+    /// 提及的频道
+    Channel,
+    /// 图片
+    Image,
+}
+
+impl ArgType {
+    /// 根据声明中的类型名获取参数类型
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(ArgType::String),
+            "text" => Some(ArgType::Text),
+            "number" => Some(ArgType::Number),
+            "integer" | "int" => Some(ArgType::Integer),
+            "boolean" | "bool" => Some(ArgType::Boolean),
+            "user" => Some(ArgType::User),
+            "channel" => Some(ArgType::Channel),
+            "image" | "img" => Some(ArgType::Image),
+            _ => None,
+        }
+    }
+
+    /// 声明中使用的类型名
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArgType::String => "string",
+            ArgType::Text => "text",
+            ArgType::Number => "number",
+            ArgType::Integer => "integer",
+            ArgType::Boolean => "boolean",
+            ArgType::User => "user",
+            ArgType::Channel => "channel",
+            ArgType::Image => "image",
+        }
+    }
+
+    // 类型的中文描述，用于错误提示
+    fn description(&self) -> &'static str {
+        match self {
+            ArgType::String | ArgType::Text => "文本",
+            ArgType::Number => "数字",
+            ArgType::Integer => "整数",
+            ArgType::Boolean => "布尔值",
+            ArgType::User => "用户",
+            ArgType::Channel => "频道",
+            ArgType::Image => "图片",
+        }
+    }
+
+    /// 将原始文本解析为该类型的值，失败时返回 `None`
+    pub fn parse(&self, raw: &str) -> Option<ArgValue> {
+        match self {
+            ArgType::String | ArgType::Text => Some(ArgValue::String(raw.to_string())),
+            ArgType::Number => raw
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(ArgValue::Number),
+            ArgType::Integer => raw.parse::<i64>().ok().map(ArgValue::Integer),
+            ArgType::Boolean => match raw.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" | "是" => Some(ArgValue::Boolean(true)),
+                "false" | "no" | "off" | "0" | "否" => Some(ArgValue::Boolean(false)),
+                _ => None,
+            },
+            ArgType::User => parse_mention(raw, "at", '@').map(ArgValue::User),
+            ArgType::Channel => parse_mention(raw, "sharp", '#').map(ArgValue::Channel),
+            ArgType::Image => parse_attribute(raw, "img", "src")
+                .or_else(|| {
+                    (raw.starts_with("http://") || raw.starts_with("https://"))
+                        .then(|| raw.to_string())
+                })
+                .map(ArgValue::Image),
+        }
+    }
+
+    /// 将分词得到的词解析为该类型的值，提及、频道与图片元素会直接转换为对应的值
+    pub fn parse_token(&self, token: &Token) -> Option<ArgValue> {
+        match (self, &token.element) {
+            (ArgType::User, Some(MessageElement::At { id, .. })) => {
+                Some(ArgValue::User(id.clone()))
+            }
+            (ArgType::Channel, Some(MessageElement::Sharp { id, .. })) => {
+                Some(ArgValue::Channel(id.clone()))
+            }
+            (ArgType::Image, Some(MessageElement::Image { src, .. })) => {
+                Some(ArgValue::Image(src.clone()))
+            }
+            (ArgType::String | ArgType::Text, _) | (_, None) => self.parse(&token.content),
+            _ => None,
+        }
+    }
+}
+
+// 解析 `<at id="..."/>` 形式的消息元素或 `@id` 形式的简写
+fn parse_mention(raw: &str, tag: &str, shorthand: char) -> Option<String> {
+    if raw.starts_with('<') {
+        return parse_attribute(raw, tag, "id");
+    }
+    raw.strip_prefix(shorthand)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+}
+
+// 读取 `<tag name="..."/>` 形式的消息元素中的属性
+fn parse_attribute(raw: &str, tag: &str, name: &str) -> Option<String> {
+    let rest = raw.strip_prefix('<')?.strip_prefix(tag)?;
+    let pattern = format!(" {}=\"", name);
+    let start = rest.find(&pattern)? + pattern.len();
+    let len = rest[start..].find('"')?;
+    let value = rest[start..start + len]
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&");
+    (!value.is_empty()).then_some(value)
+}
+
+/// 解析后的参数值，序列化为对应的 JSON 值
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ArgValue {
+    String(String),
+    Number(f64),
+    Integer(i64),
+    Boolean(bool),
+    /// 用户 ID
+    User(String),
+    /// 频道 ID
+    Channel(String),
+    /// 图片 URL
+    Image(String),
+}
+
+impl ArgValue {
+    /// 以字符串形式获取值，用户与频道返回其 ID，图片返回其 URL
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ArgValue::String(s) | ArgValue::User(s) | ArgValue::Channel(s) | ArgValue::Image(s) => {
+                Some(s)
+            }
+            _ => None,
+        }
+    }
+
+    /// 以浮点数形式获取值，整数会被转换
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ArgValue::Number(n) => Some(*n),
+            ArgValue::Integer(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ArgValue::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ArgValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ArgValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgValue::String(s) | ArgValue::User(s) | ArgValue::Channel(s) | ArgValue::Image(s) => {
+                f.write_str(s)
+            }
+            ArgValue::Number(n) => write!(f, "{}", n),
+            ArgValue::Integer(i) => write!(f, "{}", i),
+            ArgValue::Boolean(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// 位置参数的声明，例如 `<count:number>` 或 `[sides:number=6]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgumentDef {
+    pub name: String,
+    pub ty: ArgType,
+    /// `<...>` 为必选参数，`[...]` 为可选参数
+    pub required: bool,
+    /// 未提供时使用的默认值
+    pub default: Option<ArgValue>,
+}
+
+impl ArgumentDef {
+    // 解析 `<name:type=default>` 或 `[name:type=default]`，类型缺省为 string
+    fn parse(token: &str) -> Result<Self, String> {
+        let (inner, required) =
+            if let Some(inner) = token.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+                (inner, true)
+            } else if let Some(inner) = token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                (inner, false)
+            } else {
+                return Err(format!("无法识别的参数声明 {}", token));
+            };
+
+        let (spec, default) = match inner.split_once('=') {
+            Some((spec, default)) => (spec, Some(default)),
+            None => (inner, None),
+        };
+        let (name, ty) = match spec.split_once(':') {
+            Some((name, ty)) => (
+                name,
+                ArgType::from_name(ty).ok_or_else(|| format!("未知的参数类型 {}", ty))?,
+            ),
+            None => (spec, ArgType::String),
+        };
+        if name.is_empty() {
+            return Err(format!("参数声明 {} 缺少名称", token));
+        }
+        let default = default
+            .map(|raw| parse_default(name, ty, raw))
+            .transpose()?;
+
+        Ok(ArgumentDef {
+            name: name.to_string(),
+            ty,
+            required,
+            default,
+        })
+    }
+}
+
+impl fmt::Display for ArgumentDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (open, close) = if self.required {
+            ('<', '>')
+        } else {
+            ('[', ']')
+        };
+        write!(f, "{}{}", open, self.name)?;
+        if self.ty != ArgType::String {
+            write!(f, ":{}", self.ty.name())?;
+        }
+        if let Some(default) = &self.default {
+            write!(f, "={}", default)?;
+        }
+        write!(f, "{}", close)
+    }
+}
+
+// 解析声明中的默认值
+pub(crate) fn parse_default(name: &str, ty: ArgType, raw: &str) -> Result<ArgValue, String> {
+    ty.parse(raw)
+        .ok_or_else(|| format!("参数 {} 的默认值 {} 不是{}", name, raw, ty.description()))
+}
+
+/// 选项的声明
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionDef {
+    /// 选项名称，解析结果以此为键
+    pub name: String,
+    /// 可用的写法（不含前缀 `-`），总是包含名称本身
+    pub aliases: Vec<String>,
+    /// 选项值的类型，`None` 表示不带值的标志
+    pub ty: Option<ArgType>,
+    /// 未提供时使用的默认值
+    pub default: Option<ArgValue>,
+    pub description: Option<String>,
+}
+
+impl OptionDef {
+    /// 解析选项声明。
+    ///
+    /// `syntax` 由若干别名（如 `-v`、`--loud`）、可选的值声明（如 `<count:integer=1>`）
+    /// 以及描述文本组成，例如 `"-t <times:integer=1> 重复次数"`。
+    pub fn parse(name: &str, syntax: &str) -> Result<Self, String> {
+        if name.is_empty() {
+            return Err("选项缺少名称".to_string());
+        }
+        let mut option = OptionDef {
+            name: name.to_string(),
+            aliases: vec![name.to_string()],
+            ty: None,
+            default: None,
+            description: None,
+        };
+        let mut description = Vec::new();
+        for token in syntax.split_whitespace() {
+            let token_trimmed = token.trim_end_matches(',');
+            if !description.is_empty() {
+                description.push(token);
+            } else if let Some(alias) = token_trimmed
+                .strip_prefix("--")
+                .or_else(|| token_trimmed.strip_prefix('-'))
+                .filter(|alias| !alias.is_empty())
+            {
+                if !option.aliases.iter().any(|a| a == alias) {
+                    option.aliases.push(alias.to_string());
+                }
+            } else if option.ty.is_none() && (token.starts_with('<') || token.starts_with('[')) {
+                let value = ArgumentDef::parse(token)?;
+                option.ty = Some(value.ty);
+                option.default = value.default;
+            } else {
+                description.push(token);
+            }
+        }
+        if !description.is_empty() {
+            option.description = Some(description.join(" "));
+        }
+        Ok(option)
+    }
+
+    fn matches(&self, alias: &str) -> bool {
+        self.aliases.iter().any(|a| a == alias)
+    }
+}
+
+impl fmt::Display for OptionDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 短别名在前，如 `-v, --verbose`
+        let mut aliases: Vec<&String> = self.aliases.iter().collect();
+        aliases.sort_by_key(|alias| alias.chars().count());
+        let aliases: Vec<String> = aliases
+            .into_iter()
+            .map(|alias| {
+                if alias.chars().count() == 1 {
+                    format!("-{}", alias)
+                } else {
+                    format!("--{}", alias)
+                }
+            })
+            .collect();
+        write!(f, "{}", aliases.join(", "))?;
+        if let Some(ty) = self.ty {
+            write!(f, " <{}>", ty.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// 解析指令声明，例如 `roll <count:number> [sides:number=6]`。
+///
+/// 返回指令名称与参数声明列表。
+pub fn parse_declaration(declaration: &str) -> Result<(String, Vec<ArgumentDef>), String> {
+    let mut tokens = declaration.split_whitespace();
+    let name = tokens
+        .next()
+        .ok_or_else(|| "指令声明为空".to_string())?
+        .to_string();
+    let arguments = tokens
+        .map(ArgumentDef::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    validate_arguments(&arguments)?;
+    Ok((name, arguments))
+}
+
+// 检查位置参数的顺序：text 类型的参数只能是最后一个，必选参数不能位于可选参数之后
+pub(crate) fn validate_arguments(arguments: &[ArgumentDef]) -> Result<(), String> {
+    for pair in arguments.windows(2) {
+        let (last, argument) = (&pair[0], &pair[1]);
+        if last.ty == ArgType::Text {
+            return Err(format!("text 类型的参数 {} 必须是最后一个参数", last.name));
+        }
+        if argument.required && !last.required {
+            return Err(format!("必选参数 {} 不能位于可选参数之后", argument.name));
+        }
+    }
+    Ok(())
+}
+
+// 形如 -5、-1.5 的负数作为位置参数，而非短选项
+fn is_negative_number(token: &str) -> bool {
+    token.len() > 1 && token.parse::<f64>().is_ok()
+}
+
+// 解析选项值，失败时返回面向用户的错误信息
+fn parse_option_value(
+    option: &OptionDef,
+    ty: ArgType,
+    raw: Option<&Token>,
+) -> Result<ArgValue, String> {
+    let raw = raw.ok_or_else(|| format!("选项 {} 缺少值。", option.name))?;
+    ty.parse_token(raw).ok_or_else(|| {
+        format!(
+            "选项 {} 的值 {} 无效，应为{}。",
+            option.name,
+            raw.content,
+            ty.description()
+        )
+    })
+}
+
+// 与选项写在同一个词中的值，如 `--times=4` 中的 `4`
+fn inline_value(token: &Token, start: usize) -> Token {
+    Token {
+        content: token.content[start..].to_string(),
+        element: None,
+        quoted: false,
+        offset: token.offset + start,
+    }
+}
+
+// 未声明类型的值，提及、频道与图片元素保留其类型
+fn untyped_value(token: &Token) -> ArgValue {
+    match &token.element {
+        Some(MessageElement::At { id, .. }) => ArgValue::User(id.clone()),
+        Some(MessageElement::Sharp { id, .. }) => ArgValue::Channel(id.clone()),
+        Some(MessageElement::Image { src, .. }) => ArgValue::Image(src.clone()),
+        _ => ArgValue::String(token.content.clone()),
+    }
+}
+
+/// 按参数与选项声明解析指令名称之后的各个词。
+///
+/// 以引号包裹的词总是作为位置参数。text 类型的参数从其所在位置起获取 `source`
+/// 中剩余的整行原文，其后的内容不再作为选项解析；剩余部分只有一个词时取该词去掉引号后的内容。
+/// 未声明的选项按原样保留：`--key value` 的值为字符串，不带值的为 `true`。
+/// 超出声明的位置参数追加在 `arguments` 末尾。
+///
+/// # Returns
+/// 校验失败时返回面向用户的错误信息
+pub fn parse_args(
+    tokens: &[Token],
+    source: &str,
+    arguments: &[ArgumentDef],
+    options: &[OptionDef],
+) -> Result<ParsedArgs, String> {
+    let mut parsed = ParsedArgs::default();
+    let mut positionals: Vec<&Token> = Vec::new();
+    let mut rest_text: Option<String> = None;
+    let text_index = arguments.iter().position(|a| a.ty == ArgType::Text);
+    let find_option = |alias: &str| options.iter().find(|o| o.matches(alias));
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let part = token.content.as_str();
+        if let Some(long) = part
+            .strip_prefix("--")
+            .filter(|l| token.is_plain() && !l.is_empty())
+        {
+            let (key, inline) = match long.split_once('=') {
+                Some((key, _)) => (key, Some(inline_value(token, key.len() + 3))),
+                None => (long, None),
+            };
+            match find_option(key) {
+                Some(option) => {
+                    let value = match option.ty {
+                        Some(ty) => {
+                            let raw = inline.as_ref().or_else(|| {
+                                i += 1;
+                                tokens.get(i)
+                            });
+                            parse_option_value(option, ty, raw)?
+                        }
+                        None => ArgValue::Boolean(true),
+                    };
+                    parsed.options.insert(option.name.clone(), value);
+                }
+                None => {
+                    // 未声明的长选项：下一个词不是选项时视为其值，否则为标志
+                    let value = match inline {
+                        Some(value) => ArgValue::String(value.content),
+                        None => match tokens.get(i + 1) {
+                            Some(next)
+                                if !(next.content.is_empty()
+                                    || (next.is_plain() && next.content.starts_with('-'))) =>
+                            {
+                                i += 1;
+                                untyped_value(next)
+                            }
+                            _ => ArgValue::Boolean(true),
+                        },
+                    };
+                    parsed.options.insert(key.to_string(), value);
+                }
+            }
+        } else if token.is_plain()
+            && part.starts_with('-')
+            && part.len() > 1
+            && !is_negative_number(part)
+        {
+            // 短选项，可以合并书写，如 -abc
+            for (byte_idx, c) in part.char_indices().skip(1) {
+                let alias = c.to_string();
+                match find_option(&alias) {
+                    Some(option) => match option.ty {
+                        Some(ty) => {
+                            // 带值的短选项：同一词中剩余的字符或下一个词作为值
+                            let start = byte_idx + c.len_utf8();
+                            let inline = (start < part.len()).then(|| inline_value(token, start));
+                            let raw = inline.as_ref().or_else(|| {
+                                i += 1;
+                                tokens.get(i)
+                            });
+                            let value = parse_option_value(option, ty, raw)?;
+                            parsed.options.insert(option.name.clone(), value);
+                            break;
+                        }
+                        None => {
+                            parsed
+                                .options
+                                .insert(option.name.clone(), ArgValue::Boolean(true));
+                        }
+                    },
+                    None => {
+                        parsed.options.insert(alias, ArgValue::Boolean(true));
+                    }
+                }
+            }
+        } else if text_index == Some(positionals.len()) {
+            // text 参数获取剩余的整行原文
+            rest_text = Some(if i + 1 == tokens.len() {
+                token.content.clone()
+            } else {
+                source[token.offset..].trim_end().to_string()
+            });
+            break;
+        } else {
+            positionals.push(token);
+        }
+        i += 1;
+    }
+
+    fill_option_defaults(&mut parsed, options);
+
+    let mut rest = positionals.into_iter();
+    for argument in arguments {
+        let value = if argument.ty == ArgType::Text {
+            rest_text.take().map(ArgValue::String)
+        } else {
+            rest.next()
+                .map(|token| {
+                    argument.ty.parse_token(token).ok_or_else(|| {
+                        format!(
+                            "参数 {} 的值 {} 无效，应为{}。",
+                            argument.name,
+                            token.content,
+                            argument.ty.description()
+                        )
+                    })
+                })
+                .transpose()?
+        };
+        match value {
+            Some(value) => {
+                parsed.arguments.push(value.clone());
+                parsed.named.insert(argument.name.clone(), value);
+            }
+            None => match &argument.default {
+                Some(default) => {
+                    parsed.named.insert(argument.name.clone(), default.clone());
+                }
+                None if argument.required => {
+                    return Err(format!("缺少参数 {}。", argument.name));
+                }
+                None => {}
+            },
+        }
+    }
+    parsed.arguments.extend(rest.map(untyped_value));
+
+    Ok(parsed)
+}
+
+// 未出现的选项使用默认值
+fn fill_option_defaults(parsed: &mut ParsedArgs, options: &[OptionDef]) {
+    for option in options {
+        if let Some(default) = &option.default {
+            parsed
+                .options
+                .entry(option.name.clone())
+                .or_insert_with(|| default.clone());
+        }
+    }
+}
+
+// 平台已拆分好的值：用户与频道类型的值为 ID，转换为对应的元素
+fn native_token(raw: &str, ty: Option<ArgType>, offset: usize) -> Token {
+    let element = match ty {
+        Some(ArgType::User) => Some(MessageElement::At {
+            id: raw.to_string(),
+            name: None,
+            role: None,
+            at_type: None,
+        }),
+        Some(ArgType::Channel) => Some(MessageElement::Sharp {
+            id: raw.to_string(),
+            name: None,
+        }),
+        _ => None,
+    };
+    Token {
+        content: raw.to_string(),
+        element: element.filter(|_| !raw.is_empty() && !raw.starts_with(['<', '@', '#'])),
+        quoted: true,
+        offset,
+    }
+}
+
+/// 按参数与选项声明解析平台原生指令（`interaction/command` 事件）的参数。
+///
+/// 参数与选项已由平台拆分，不再分词，位置参数不会被识别为选项；用户与频道类型的值可以直接为 ID。
+/// 不带值的选项以 `false` 等值表示未启用。校验规则与 [`parse_args`] 相同。
+///
+/// # Returns
+/// 校验失败时返回面向用户的错误信息
+pub fn parse_argv(
+    argv: &Argv,
+    arguments: &[ArgumentDef],
+    options: &[OptionDef],
+) -> Result<ParsedArgs, String> {
+    let mut source = String::new();
+    let mut tokens = Vec::new();
+    for (i, raw) in argv.argument.iter().enumerate() {
+        if !source.is_empty() {
+            source.push(' ');
+        }
+        tokens.push(native_token(
+            raw,
+            arguments.get(i).map(|a| a.ty),
+            source.len(),
+        ));
+        source.push_str(raw);
+    }
+    let mut parsed = parse_args(&tokens, &source, arguments, &[])?;
+
+    for (key, raw) in &argv.options {
+        let Some(option) = options.iter().find(|o| o.matches(key)) else {
+            parsed
+                .options
+                .insert(key.clone(), ArgValue::String(raw.clone()));
+            continue;
+        };
+        let value = match option.ty {
+            Some(ty) => parse_option_value(option, ty, Some(&native_token(raw, Some(ty), 0)))?,
+            None => match ArgType::Boolean.parse(raw) {
+                Some(ArgValue::Boolean(false)) => continue,
+                _ => ArgValue::Boolean(true),
+            },
+        };
+        parsed.options.insert(option.name.clone(), value);
+    }
+    fill_option_defaults(&mut parsed, options);
+    Ok(parsed)
+}
+
+/// 指令的结构描述，供适配器在支持的平台上注册原生指令（如斜杠指令）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandSchema {
+    /// 指令名称，子指令以 `.` 分隔路径
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: Vec<ArgumentSchema>,
+    pub options: Vec<OptionSchema>,
+}
+
+/// 位置参数的结构描述
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArgumentSchema {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: ArgType,
+    pub required: bool,
+    pub default: Option<ArgValue>,
+}
+
+/// 选项的结构描述
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OptionSchema {
+    pub name: String,
+    /// 可用的写法（不含前缀 `-`），总是包含名称本身
+    pub aliases: Vec<String>,
+    /// 选项值的类型，`None` 表示不带值的标志
+    #[serde(rename = "type")]
+    pub ty: Option<ArgType>,
+    pub default: Option<ArgValue>,
+    pub description: Option<String>,
+}
+
+impl From<&ArgumentDef> for ArgumentSchema {
+    fn from(argument: &ArgumentDef) -> Self {
+        ArgumentSchema {
+            name: argument.name.clone(),
+            ty: argument.ty,
+            required: argument.required,
+            default: argument.default.clone(),
+        }
+    }
+}
+
+impl From<&OptionDef> for OptionSchema {
+    fn from(option: &OptionDef) -> Self {
+        OptionSchema {
+            name: option.name.clone(),
+            aliases: option.aliases.clone(),
+            ty: option.ty,
+            default: option.default.clone(),
+            description: option.description.clone(),
+        }
+    }
+}
+
+/// 生成指令的用法说明，例如 `roll <count:number> [sides:number]`
+pub fn usage(name: &str, arguments: &[ArgumentDef]) -> String {
+    let mut usage = name.to_string();
+    for argument in arguments {
+        usage.push(' ');
+        usage.push_str(&argument.to_string());
+    }
+    usage
+}
@@ -0,0 +1,69 @@
+pub use regex::Regex;
+
+/// 快捷方式的匹配规则
+#[derive(Debug, Clone)]
+pub enum ShortcutPattern {
+    /// 去掉首尾空白后与消息完全相同的短语
+    Literal(String),
+    /// 与消息中的内容匹配的正则表达式，需要匹配整条消息时请使用 `^` 与 `$`
+    Regex(Regex),
+}
+
+impl From<&str> for ShortcutPattern {
+    fn from(phrase: &str) -> Self {
+        ShortcutPattern::Literal(phrase.to_string())
+    }
+}
+
+impl From<String> for ShortcutPattern {
+    fn from(phrase: String) -> Self {
+        ShortcutPattern::Literal(phrase)
+    }
+}
+
+impl From<Regex> for ShortcutPattern {
+    fn from(regex: Regex) -> Self {
+        ShortcutPattern::Regex(regex)
+    }
+}
+
+/// 无需前缀即可触发指令的快捷方式
+#[derive(Debug, Clone)]
+pub struct Shortcut {
+    pub pattern: ShortcutPattern,
+    /// 预设的参数与选项，按指令文本的规则解析，例如 `Tokyo -l`
+    pub args: String,
+}
+
+impl Shortcut {
+    /// 用快捷方式匹配消息文本。
+    ///
+    /// # Returns
+    /// 匹配成功时返回指令的参数文本：预设参数之后依次跟随正则表达式中非空的捕获组，
+    /// 每个捕获组作为一个完整的参数
+    pub fn matches(&self, text: &str) -> Option<String> {
+        let text = text.trim();
+        match &self.pattern {
+            ShortcutPattern::Literal(phrase) => (text == phrase.trim()).then(|| self.args.clone()),
+            ShortcutPattern::Regex(regex) => {
+                let captures = regex.captures(text)?;
+                let mut args = self.args.clone();
+                for group in captures.iter().skip(1).flatten() {
+                    if group.is_empty() {
+                        continue;
+                    }
+                    if !args.is_empty() {
+                        args.push(' ');
+                    }
+                    args.push_str(&quote(group.as_str()));
+                }
+                Some(args)
+            }
+        }
+    }
+}
+
+// 用双引号包裹捕获的内容，使其成为一个词且不会被识别为选项
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::tokenizer::Tokens;
+use crate::message::MessageElement;
+use crate::session::Session;
+use crate::sync::MutexExt;
+
+/// 等待用户确认推测指令的最长时间
+pub const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 未知指令的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SuggestionMode {
+    /// 不作处理，交由后续逻辑
+    Disabled,
+    /// 回复相近的指令名称
+    #[default]
+    Suggest,
+    /// 只有一个相近的指令时，请用户发送句号确认，确认后以相同的参数执行该指令
+    Confirm,
+}
+
+// 等待确认的推测指令，指令名称已替换为推测结果
+#[derive(Debug)]
+struct PendingSuggestion {
+    tokens: Tokens,
+    expires_at: Instant,
+}
+
+/// 未知指令推测的配置，存储在共享状态中
+#[derive(Debug, Clone, Default)]
+pub struct SuggestionConfig {
+    pub mode: SuggestionMode,
+    pending: Arc<Mutex<HashMap<String, PendingSuggestion>>>, // 平台:频道:用户 -> 待确认的指令
+}
+
+impl SuggestionConfig {
+    // 同一用户在同一频道中的推测互相覆盖
+    fn key(session: &Session) -> String {
+        format!(
+            "{}:{}:{}",
+            session.platform, session.channel_id, session.user_id
+        )
+    }
+
+    /// 记录等待确认的指令
+    pub(crate) fn remember(&self, session: &Session, tokens: Tokens) {
+        let mut pending = self.pending.lock_recover();
+        let now = Instant::now();
+        pending.retain(|_, suggestion| suggestion.expires_at > now);
+        pending.insert(
+            Self::key(session),
+            PendingSuggestion {
+                tokens,
+                expires_at: now + CONFIRM_TIMEOUT,
+            },
+        );
+    }
+
+    /// 取出该用户等待确认的指令。
+    ///
+    /// 只有消息为句号且未超时才返回指令，任何其他消息都会使推测失效。
+    pub(crate) fn take_confirmed(
+        &self,
+        session: &Session,
+        elements: &[MessageElement],
+    ) -> Option<Tokens> {
+        let mut pending = self.pending.lock_recover();
+        if pending.is_empty() {
+            return None;
+        }
+        let suggestion = pending.remove(&Self::key(session))?;
+        let text: String = elements
+            .iter()
+            .filter_map(|element| match element {
+                MessageElement::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        (matches!(text.trim(), "." | "。") && suggestion.expires_at > Instant::now())
+            .then_some(suggestion.tokens)
+    }
+}
+
+// 允许相邻字符交换的编辑距离（Optimal String Alignment）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// 在候选名称中找出与输入最相近的名称（按名称排序）。
+///
+/// 编辑距离不超过较长名称长度的三分之一（至少为 1）的候选才会被考虑，
+/// 只返回距离最小的那些候选。
+pub fn suggest<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut best: Vec<String> = Vec::new();
+    let mut best_distance = usize::MAX;
+    for candidate in candidates {
+        let distance = edit_distance(input, candidate);
+        let limit = (input.chars().count().max(candidate.chars().count()) / 3).max(1);
+        if distance == 0 || distance > limit || distance > best_distance {
+            continue;
+        }
+        if distance < best_distance {
+            best_distance = distance;
+            best.clear();
+        }
+        if !best.iter().any(|name| name == candidate) {
+            best.push(candidate.to_string());
+        }
+    }
+    best.sort();
+    best
+}
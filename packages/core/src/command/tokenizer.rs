@@ -0,0 +1,255 @@
+use crate::message::MessageElement;
+use std::collections::HashMap;
+
+/// 指令文本中的一个词
+#[derive(Debug, Clone)]
+pub struct Token {
+    /// 词的内容，已去掉引号与转义符；非文本元素为其标记形式，如 `<at id="42"/>`
+    pub content: String,
+    /// 词对应的非文本消息元素，如提及与图片
+    pub element: Option<MessageElement>,
+    /// 词是否以引号或转义符开头，这样的词不会被识别为选项
+    pub quoted: bool,
+    /// 词在源文本中的起始位置（字节）
+    pub offset: usize,
+}
+
+impl Token {
+    /// 词是否为普通文本，即不是消息元素且未被引号包裹
+    pub fn is_plain(&self) -> bool {
+        self.element.is_none() && !self.quoted
+    }
+}
+
+/// 分词的结果
+#[derive(Debug, Clone, Default)]
+pub struct Tokens {
+    /// 消息的源文本，非文本元素以标记形式表示，用于截取剩余的整行文本
+    pub source: String,
+    pub tokens: Vec<Token>,
+}
+
+/// 将消息元素切分为词。
+///
+/// 文本按空白分词，支持 `"..."`、`'...'` 与 `“...”` 包裹含空白的词，
+/// 引号之外与双引号之内可以用 `\` 转义下一个字符。
+/// 只有词首的引号会开始引用，词中的引号（如 `don't`）按原样保留；
+/// 没有闭合的引号同样视为普通字符，其后的文本照常分词。
+/// 提及、频道、图片等非文本元素各自成为一个词，引用与作者信息会被忽略，
+/// 粗体等修饰元素只保留其中的文本。
+/// 只提供文本内容的消息中，位于词首的 `<at id="42"/>`、`<sharp id="..."/>` 与
+/// `<img src="..."/>` 标记同样被识别为对应的元素。
+pub fn tokenize(elements: &[MessageElement]) -> Tokens {
+    let mut tokenizer = Tokenizer::default();
+    tokenizer.push_elements(elements);
+    tokenizer.finish_token();
+    Tokens {
+        source: tokenizer.source,
+        tokens: tokenizer.tokens,
+    }
+}
+
+/// 非文本元素的标记形式，用于在文本中表示该元素
+pub fn render(element: &MessageElement) -> String {
+    let escape = |value: &str| value.replace('&', "&amp;").replace('"', "&quot;");
+    match element {
+        MessageElement::Text { text } => text.clone(),
+        MessageElement::At { id, .. } => format!("<at id=\"{}\"/>", escape(id)),
+        MessageElement::Sharp { id, .. } => format!("<sharp id=\"{}\"/>", escape(id)),
+        MessageElement::Image { src, .. } => format!("<img src=\"{}\"/>", escape(src)),
+        MessageElement::Audio { src, .. } => format!("<audio src=\"{}\"/>", escape(src)),
+        MessageElement::Video { src, .. } => format!("<video src=\"{}\"/>", escape(src)),
+        MessageElement::File { src, .. } => format!("<file src=\"{}\"/>", escape(src)),
+        MessageElement::LineBreak => "\n".to_string(),
+        _ => String::new(),
+    }
+}
+
+#[derive(Default)]
+struct Tokenizer {
+    source: String,
+    tokens: Vec<Token>,
+    current: Option<Token>,
+    // 当前所在引号的结束符
+    closing_quote: Option<char>,
+    escaped: bool,
+}
+
+impl Tokenizer {
+    fn push_elements(&mut self, elements: &[MessageElement]) {
+        for element in elements {
+            match element {
+                MessageElement::Text { text } => self.push_text(text),
+                MessageElement::LineBreak => self.push_text("\n"),
+                MessageElement::At { .. }
+                | MessageElement::Sharp { .. }
+                | MessageElement::Image { .. }
+                | MessageElement::Audio { .. }
+                | MessageElement::Video { .. }
+                | MessageElement::File { .. } => self.push_element(element),
+                MessageElement::Bold { children }
+                | MessageElement::Italic { children }
+                | MessageElement::Underline { children }
+                | MessageElement::Strikethrough { children }
+                | MessageElement::Spoiler { children }
+                | MessageElement::Code { children }
+                | MessageElement::Superscript { children }
+                | MessageElement::Subscript { children }
+                | MessageElement::Paragraph { children }
+                | MessageElement::Link { children, .. }
+                | MessageElement::Span { children, .. }
+                | MessageElement::Div { children, .. } => self.push_elements(children),
+                // 引用、作者、按钮等元素不属于指令文本
+                _ => {}
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let mut markup_end = 0;
+        for (index, c) in text.char_indices() {
+            if index < markup_end {
+                continue;
+            }
+            // 引号、转义符与其他词之外的元素标记整体作为一个元素词
+            if c == '<'
+                && self.current.is_none()
+                && let Some((len, element)) = parse_markup(&text[index..])
+            {
+                markup_end = index + len;
+                self.push_token(text[index..markup_end].to_string(), element);
+                continue;
+            }
+            let offset = self.source.len();
+            self.source.push(c);
+            if self.escaped {
+                self.escaped = false;
+                self.token(offset, true).content.push(c);
+                continue;
+            }
+            match self.closing_quote {
+                Some(closing) if c == closing => self.closing_quote = None,
+                // 单引号内的内容按原样保留
+                Some('"') if c == '\\' => self.escaped = true,
+                Some(_) => self.token(offset, true).content.push(c),
+                None => match c {
+                    '\\' => {
+                        self.token(offset, true);
+                        self.escaped = true;
+                    }
+                    '"' | '\'' | '“' if self.current.is_none() => {
+                        self.token(offset, true);
+                        self.closing_quote = Some(if c == '“' { '”' } else { c });
+                    }
+                    c if c.is_whitespace() => self.finish_token(),
+                    c => self.token(offset, false).content.push(c),
+                },
+            }
+        }
+    }
+
+    fn push_element(&mut self, element: &MessageElement) {
+        self.push_token(render(element), element.clone());
+    }
+
+    // 添加一个元素词，content 为其在源文本中的标记形式
+    fn push_token(&mut self, content: String, element: MessageElement) {
+        self.finish_token();
+        let offset = self.source.len();
+        self.source.push_str(&content);
+        self.tokens.push(Token {
+            content,
+            element: Some(element),
+            quoted: false,
+            offset,
+        });
+    }
+
+    // 获取正在构建的词，不存在时从 offset 处开始一个新词
+    fn token(&mut self, offset: usize, quoted: bool) -> &mut Token {
+        self.current.get_or_insert_with(|| Token {
+            content: String::new(),
+            element: None,
+            quoted,
+            offset,
+        })
+    }
+
+    fn finish_token(&mut self) {
+        // 引号没有闭合时将其作为普通字符，重新切分引号之后的文本
+        if self.closing_quote.take().is_some()
+            && let Some(token) = self.current.take()
+        {
+            let rest = self.source.split_off(token.offset);
+            let mut chars = rest.chars();
+            let quote = chars.next().unwrap_or_default();
+            self.escaped = false;
+            self.source.push(quote);
+            self.token(token.offset, false).content.push(quote);
+            self.push_text(chars.as_str());
+            // 剩余的文本中可能还有未闭合的引号
+            return self.finish_token();
+        }
+        if self.escaped {
+            // 末尾孤立的转义符按原样保留
+            if let Some(token) = self.current.as_mut() {
+                token.content.push('\\');
+            }
+        }
+        self.escaped = false;
+        self.closing_quote = None;
+        if let Some(token) = self.current.take() {
+            self.tokens.push(token);
+        }
+    }
+}
+
+// 解析文本开头的 `<tag name="value"/>` 形式的元素标记，返回标记的长度与对应的元素
+fn parse_markup(text: &str) -> Option<(usize, MessageElement)> {
+    let rest = text.strip_prefix('<')?;
+    let tag_len = rest.find(|c: char| !c.is_ascii_alphabetic())?;
+    let (tag, mut rest) = rest.split_at(tag_len);
+    let mut attributes = HashMap::new();
+    loop {
+        let trimmed = rest.trim_start();
+        if let Some(after) = trimmed.strip_prefix("/>") {
+            rest = after;
+            break;
+        }
+        // 属性之间需要空白分隔
+        if trimmed.len() == rest.len() {
+            return None;
+        }
+        let (name, value) = trimmed.split_once("=\"")?;
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return None;
+        }
+        let len = value.find('"')?;
+        let unescaped = value[..len].replace("&quot;", "\"").replace("&amp;", "&");
+        attributes.insert(name, unescaped);
+        rest = &value[len + 1..];
+    }
+    let mut attribute = |name: &str| attributes.remove(name);
+    let element = match tag {
+        "at" => MessageElement::At {
+            id: attribute("id").unwrap_or_default(),
+            name: attribute("name"),
+            role: attribute("role"),
+            at_type: attribute("type"),
+        },
+        "sharp" => MessageElement::Sharp {
+            id: attribute("id")?,
+            name: attribute("name"),
+        },
+        "img" => MessageElement::Image {
+            src: attribute("src")?,
+            title: attribute("title"),
+            width: attribute("width").and_then(|v| v.parse().ok()),
+            height: attribute("height").and_then(|v| v.parse().ok()),
+            cache: attribute("cache").and_then(|v| v.parse().ok()),
+            timeout: attribute("timeout"),
+        },
+        _ => return None,
+    };
+    Some((text.len() - rest.len(), element))
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use super::Command;
+use crate::error::FrameworkResult;
+use crate::session::Session;
+use crate::sync::MutexExt;
+
+/// 冷却与调用次数的统计范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UsageScope {
+    /// 每个用户单独统计
+    User,
+    /// 每个频道单独统计
+    Channel,
+    /// 每个群组单独统计，私聊按频道统计
+    Guild,
+    /// 所有调用共同统计
+    Global,
+}
+
+impl UsageScope {
+    // 会话在该范围下的统计键
+    fn key(&self, session: &Session) -> String {
+        match self {
+            UsageScope::User => format!("user:{}:{}", session.platform, session.user_id),
+            UsageScope::Channel => format!("channel:{}:{}", session.platform, session.channel_id),
+            UsageScope::Guild if session.guild_id.is_empty() => {
+                format!("channel:{}:{}", session.platform, session.channel_id)
+            }
+            UsageScope::Guild => format!("guild:{}:{}", session.platform, session.guild_id),
+            UsageScope::Global => "global".to_string(),
+        }
+    }
+}
+
+/// 两次调用之间的最短间隔
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cooldown {
+    pub duration: Duration,
+    pub scope: UsageScope,
+}
+
+/// 一段时间内的最大调用次数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsageLimit {
+    pub max: u32,
+    pub period: Duration,
+    pub scope: UsageScope,
+}
+
+/// 一个统计键的调用记录，时间均为 Unix 毫秒时间戳
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// 最近一次调用的时间
+    pub last_used_ms: i64,
+    /// 当前统计周期的开始时间
+    pub window_start_ms: i64,
+    /// 当前统计周期内的调用次数
+    pub count: u32,
+}
+
+/// 调用记录的存储后端。
+///
+/// 默认使用 [`MemoryUsageStore`]，需要在重启后保留记录时可以实现此 trait 接入持久化存储，
+/// 并通过 `Context::set_usage_store` 设置。
+#[async_trait]
+pub trait UsageStore: Send + Sync {
+    /// 读取记录，不存在或已过期时返回 `None`
+    async fn get(&self, key: &str) -> FrameworkResult<Option<UsageRecord>>;
+    /// 写入记录，`ttl` 之后记录不再有用，存储可以将其清除
+    async fn set(&self, key: &str, record: UsageRecord, ttl: Duration) -> FrameworkResult<()>;
+}
+
+#[async_trait]
+impl<S: UsageStore + ?Sized> UsageStore for Arc<S> {
+    async fn get(&self, key: &str) -> FrameworkResult<Option<UsageRecord>> {
+        (**self).get(key).await
+    }
+
+    async fn set(&self, key: &str, record: UsageRecord, ttl: Duration) -> FrameworkResult<()> {
+        (**self).set(key, record, ttl).await
+    }
+}
+
+// 内存存储的记录数超过此值时清理过期记录
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// 将调用记录保存在内存中的存储，重启后记录会丢失
+#[derive(Debug, Default)]
+pub struct MemoryUsageStore {
+    records: Mutex<HashMap<String, (UsageRecord, Instant)>>, // 键 -> (记录, 过期时间)
+}
+
+impl MemoryUsageStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UsageStore for MemoryUsageStore {
+    async fn get(&self, key: &str) -> FrameworkResult<Option<UsageRecord>> {
+        let records = self.records.lock_recover();
+        Ok(records
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at > Instant::now())
+            .map(|(record, _)| *record))
+    }
+
+    async fn set(&self, key: &str, record: UsageRecord, ttl: Duration) -> FrameworkResult<()> {
+        let mut records = self.records.lock_recover();
+        let now = Instant::now();
+        if records.len() >= PRUNE_THRESHOLD {
+            records.retain(|_, (_, expires_at)| *expires_at > now);
+        }
+        records.insert(key.to_string(), (record, now + ttl));
+        Ok(())
+    }
+}
+
+/// 指令调用统计的配置，存储在共享状态中
+#[derive(Clone)]
+pub struct UsageConfig {
+    pub store: Arc<dyn UsageStore>,
+    // 检查与记录之间持有，避免并发调用同时通过检查
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl UsageConfig {
+    pub fn new(store: Arc<dyn UsageStore>) -> Self {
+        UsageConfig {
+            store,
+            lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self::new(Arc::new(MemoryUsageStore::new()))
+    }
+}
+
+impl fmt::Debug for UsageConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UsageConfig")
+            .field("store", &"Arc<dyn UsageStore>")
+            .finish()
+    }
+}
+
+// 当前的毫秒时间戳。首次调用时以系统时间为起点，之后按 tokio 的时钟推进，
+// 因此不受系统时间调整的影响，测试中也可以通过 `tokio::time::pause` 控制
+fn now_ms() -> i64 {
+    static START: OnceLock<(i64, Instant)> = OnceLock::new();
+    let (start_ms, start) = START.get_or_init(|| {
+        let start_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as i64);
+        (start_ms, Instant::now())
+    });
+    start_ms + Instant::now().duration_since(*start).as_millis() as i64
+}
+
+// 距离 since 经过 duration 之后还需等待的时间
+fn remaining(since_ms: i64, duration: Duration, now_ms: i64) -> Option<Duration> {
+    let elapsed = Duration::from_millis(now_ms.saturating_sub(since_ms).max(0) as u64);
+    duration.checked_sub(elapsed).filter(|left| !left.is_zero())
+}
+
+/// 将等待时间格式化为面向用户的文本，不足一秒按一秒计
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    match (hours, minutes, seconds) {
+        (0, 0, seconds) => format!("{} 秒", seconds),
+        (0, minutes, 0) => format!("{} 分钟", minutes),
+        (0, minutes, seconds) => format!("{} 分钟 {} 秒", minutes, seconds),
+        (hours, 0, _) => format!("{} 小时", hours),
+        (hours, minutes, _) => format!("{} 小时 {} 分钟", hours, minutes),
+    }
+}
+
+// 冷却与调用次数记录的更新：(键, 记录, 有效期)
+type UsageUpdates = Vec<(String, UsageRecord, Duration)>;
+
+/// 检查指令的冷却与调用次数限制，不记录本次调用。
+///
+/// 用于执行前钩子之前的预先检查，钩子允许执行后再通过 [`check_and_record`] 记录。
+///
+/// # Returns
+/// 超出限制时返回面向用户的提示
+pub async fn check(
+    config: &UsageConfig,
+    command: &Command,
+    session: &Session,
+) -> FrameworkResult<Option<String>> {
+    if command.cooldown.is_none() && command.usage_limits.is_empty() {
+        return Ok(None);
+    }
+    Ok(evaluate(config, command, session, now_ms()).await?.err())
+}
+
+/// 检查指令的冷却与调用次数限制，通过时记录本次调用。
+///
+/// # Returns
+/// 超出限制时返回面向用户的提示
+pub async fn check_and_record(
+    config: &UsageConfig,
+    command: &Command,
+    session: &Session,
+) -> FrameworkResult<Option<String>> {
+    if command.cooldown.is_none() && command.usage_limits.is_empty() {
+        return Ok(None);
+    }
+    let _guard = config.lock.lock().await;
+    match evaluate(config, command, session, now_ms()).await? {
+        Ok(updates) => {
+            for (key, record, ttl) in updates {
+                config.store.set(&key, record, ttl).await?;
+            }
+            Ok(None)
+        }
+        Err(reply) => Ok(Some(reply)),
+    }
+}
+
+// 计算本次调用后的记录，超出限制时返回面向用户的提示
+async fn evaluate(
+    config: &UsageConfig,
+    command: &Command,
+    session: &Session,
+    now: i64,
+) -> FrameworkResult<Result<UsageUpdates, String>> {
+    let mut updates = Vec::new();
+    // 不同上下文中的同名指令分别计数
+    let identity = command.identity();
+    if let Some(cooldown) = &command.cooldown {
+        let key = format!("cooldown:{}:{}", identity, cooldown.scope.key(session));
+        let record = config.store.get(&key).await?;
+        if let Some(left) =
+            record.and_then(|record| remaining(record.last_used_ms, cooldown.duration, now))
+        {
+            return Ok(Err(format!(
+                "指令 {} 冷却中，请在 {} 后重试。",
+                command.name,
+                format_duration(left)
+            )));
+        }
+        let record = UsageRecord {
+            last_used_ms: now,
+            window_start_ms: now,
+            count: 1,
+        };
+        updates.push((key, record, cooldown.duration));
+    }
+    for limit in &command.usage_limits {
+        let key = format!(
+            "usage:{}:{}:{}",
+            identity,
+            limit.period.as_millis(),
+            limit.scope.key(session)
+        );
+        // 统计周期已结束时重新开始计数
+        let mut record = config
+            .store
+            .get(&key)
+            .await?
+            .filter(|record| remaining(record.window_start_ms, limit.period, now).is_some())
+            .unwrap_or(UsageRecord {
+                last_used_ms: now,
+                window_start_ms: now,
+                count: 0,
+            });
+        if record.count >= limit.max {
+            let left = remaining(record.window_start_ms, limit.period, now).unwrap_or_default();
+            return Ok(Err(format!(
+                "指令 {} 的调用次数已达上限，请在 {} 后重试。",
+                command.name,
+                format_duration(left)
+            )));
+        }
+        record.count += 1;
+        record.last_used_ms = now;
+        updates.push((key, record, limit.period));
+    }
+    Ok(Ok(updates))
+}
@@ -5,6 +5,12 @@ pub struct BasicConfig {
     pub log_level: String,
     pub ignore_self_message: bool,
     pub prefix: Vec<String>,
+    /// 机器人的昵称，以昵称开头的消息无需前缀即可触发指令
+    #[serde(default)]
+    pub nickname: Vec<String>,
+    /// 指令的执行时限（秒），未设置时不限制
+    #[serde(default)]
+    pub command_timeout: Option<u64>,
 }
 
 #[cfg(test)]
@@ -1,26 +1,46 @@
 pub mod filter;
 pub mod listener;
+pub mod middleware;
+pub mod roles;
 pub mod state;
 
 // TODO: 完善上下文系统
+use futures_util::FutureExt;
+use futures_util::future::join_all;
 use std::any::Any;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
 use std::sync::{Arc, Mutex, RwLock}; // Mutex 用于回调的内部可变性
-use uuid::Uuid; // Mutex 用于回调的内部可变性
+use std::time::Duration;
+use uuid::Uuid;
 
 use crate::bot::Bot;
 use crate::command::CommandBuilder;
+use crate::command::permission::PermissionResolver;
+use crate::command::suggest::SuggestionMode;
+use crate::command::usage::{UsageConfig, UsageStore};
 use crate::context::{
     filter::ContextFilter,
-    listener::{ListenerAction, ListenerHandle, ListenerId, RegisteredListener},
+    listener::{
+        EmitMode, EventArgs, ListenerAction, ListenerHandle, ListenerId, ListenerOptions,
+        RegisteredListener,
+    },
+    middleware::{MiddlewareHandle, Next, RegisteredMiddleware},
     state::EventSystemSharedState,
 };
+use crate::error::FrameworkResult;
+use crate::event::{CommandAfterExecute, CommandBeforeExecute, ErrorSource, Event, InternalError};
 use crate::session::Session;
+use crate::sync::{MutexExt, RwLockExt, panic_message};
+
+// 已隔离 panic 的监听器调用，返回 bail 监听器的熔断值
+type GuardedFuture<'a> =
+    Pin<Box<dyn Future<Output = Option<Box<dyn Any + Send + Sync>>> + Send + 'a>>;
 
 // 事件上下文
 #[derive(Clone)]
 pub struct Context {
-    /// 当前应用的全部机器人实例
-    pub bots: Arc<Mutex<Vec<Arc<Bot>>>>,
     /// 当前上下文的过滤器设置
     pub current_filter: ContextFilter,
     /// 对共享状态的引用
@@ -30,7 +50,6 @@ pub struct Context {
 impl Context {
     pub fn new_root(shared_state: Arc<RwLock<EventSystemSharedState>>) -> Self {
         Context {
-            bots: Arc::new(Mutex::new(Vec::new())),
             current_filter: ContextFilter::new(), // 根上下文的过滤器是空的
             shared_state,
         }
@@ -40,7 +59,6 @@ impl Context {
     pub fn user(&self, user_id: &str) -> Self {
         // 创建只针对特定用户的子上下文
         Context {
-            bots: self.bots.clone(),
             current_filter: self.current_filter.clone().user(user_id),
             shared_state: Arc::clone(&self.shared_state),
         }
@@ -48,7 +66,6 @@ impl Context {
     pub fn guild(&self, guild_id: &str) -> Self {
         // 创建只针对特定群组的子上下文
         Context {
-            bots: self.bots.clone(),
             current_filter: self.current_filter.clone().guild(guild_id),
             shared_state: Arc::clone(&self.shared_state),
         }
@@ -56,15 +73,35 @@ impl Context {
     pub fn platform(&self, platform: &str) -> Self {
         // 创建只针对特定平台的子上下文
         Context {
-            bots: self.bots.clone(),
             current_filter: self.current_filter.clone().platform(platform),
             shared_state: Arc::clone(&self.shared_state),
         }
     }
+    pub fn channel(&self, channel_id: &str) -> Self {
+        // 创建只针对特定频道的子上下文
+        Context {
+            current_filter: self.current_filter.clone().channel(channel_id),
+            shared_state: Arc::clone(&self.shared_state),
+        }
+    }
+    pub fn self_id(&self, self_id: &str) -> Self {
+        // 创建只针对特定机器人账号的子上下文
+        Context {
+            current_filter: self.current_filter.clone().self_id(self_id),
+            shared_state: Arc::clone(&self.shared_state),
+        }
+    }
+    pub fn role(&self, role_id: &str) -> Self {
+        // 创建只针对拥有特定角色的成员的子上下文
+        // 角色在匹配此上下文的过滤器前按需获取，见 Session::resolve_roles
+        Context {
+            current_filter: self.current_filter.clone().role(role_id),
+            shared_state: Arc::clone(&self.shared_state),
+        }
+    }
     pub fn private(&self) -> Self {
         // 创建只针对私聊的子上下文
         Context {
-            bots: self.bots.clone(),
             current_filter: self.current_filter.clone().private(),
             shared_state: Arc::clone(&self.shared_state),
         }
@@ -72,48 +109,301 @@ impl Context {
     pub fn group(&self) -> Self {
         // 创建只针对群聊的子上下文 (与 private 相对)
         Context {
-            bots: self.bots.clone(),
             current_filter: self.current_filter.clone().group(),
             shared_state: Arc::clone(&self.shared_state),
         }
     }
+    /// 创建匹配当前上下文或另一上下文的子上下文
+    pub fn union(&self, other: &Context) -> Self {
+        Context {
+            current_filter: self
+                .current_filter
+                .clone()
+                .union(other.current_filter.clone()),
+            shared_state: Arc::clone(&self.shared_state),
+        }
+    }
+    /// 创建同时匹配当前上下文与另一上下文的子上下文
+    pub fn intersect(&self, other: &Context) -> Self {
+        Context {
+            current_filter: self
+                .current_filter
+                .clone()
+                .intersect(other.current_filter.clone()),
+            shared_state: Arc::clone(&self.shared_state),
+        }
+    }
+    /// 创建匹配当前上下文但排除另一上下文的子上下文
+    ///
+    /// 例如 `ctx.exclude(&ctx.guild("X"))` 表示除群组 X 以外的所有会话。
+    pub fn exclude(&self, other: &Context) -> Self {
+        Context {
+            current_filter: self
+                .current_filter
+                .clone()
+                .exclude(other.current_filter.clone()),
+            shared_state: Arc::clone(&self.shared_state),
+        }
+    }
+    /// 创建只匹配满足自定义条件的会话的子上下文
+    pub fn filter<F>(&self, predicate: F) -> Self
+    where
+        F: Fn(&Session) -> bool + Send + Sync + 'static,
+    {
+        Context {
+            current_filter: self.current_filter.clone().predicate(predicate),
+            shared_state: Arc::clone(&self.shared_state),
+        }
+    }
     // --- 上下文派生方法结束 ---
 
+    // --- 机器人注册表 ---
+    /// 获取当前应用的全部机器人实例
+    pub fn bots(&self) -> Vec<Arc<Bot>> {
+        self.shared_state.read_recover().bots.clone()
+    }
+
+    /// 根据平台与机器人账号查找机器人实例
+    pub fn bot(&self, platform: &str, self_id: &str) -> Option<Arc<Bot>> {
+        self.shared_state
+            .read_recover()
+            .bots
+            .iter()
+            .find(|bot| bot.platform == platform && bot.self_id() == self_id)
+            .cloned()
+    }
+
+    /// 获取某个平台下的全部机器人实例
+    pub fn bots_by_platform(&self, platform: &str) -> Vec<Arc<Bot>> {
+        self.shared_state
+            .read_recover()
+            .bots
+            .iter()
+            .filter(|bot| bot.platform == platform)
+            .cloned()
+            .collect()
+    }
+
+    /// 注册一个机器人实例，并发射 `bot-added` 事件（参数为 `Arc<Bot>`）
+    ///
+    /// 事件以异步方式发射，同步与异步监听器都会被执行。
+    pub async fn add_bot(&self, bot: Arc<Bot>) {
+        self.shared_state
+            .write_recover()
+            .bots
+            .push(Arc::clone(&bot));
+        self.emit_async("bot-added", None, vec![Box::new(bot)], EmitMode::Serial)
+            .await;
+    }
+
+    /// 移除一个机器人实例，并发射 `bot-removed` 事件（参数为 `Arc<Bot>`）
+    ///
+    /// 事件以异步方式发射，同步与异步监听器都会被执行。
+    ///
+    /// # Returns
+    /// 如果该实例存在于注册表中，返回 `true`
+    pub async fn remove_bot(&self, bot: &Arc<Bot>) -> bool {
+        let removed = {
+            let mut state = self.shared_state.write_recover();
+            let len_before = state.bots.len();
+            state.bots.retain(|b| !Arc::ptr_eq(b, bot));
+            state.bots.len() != len_before
+        };
+        if removed {
+            self.emit_async(
+                "bot-removed",
+                None,
+                vec![Box::new(Arc::clone(bot))],
+                EmitMode::Serial,
+            )
+            .await;
+        }
+        removed
+    }
+    // --- 机器人注册表结束 ---
+
     /// 开始定义一个新指令。
     ///
     /// 指令将自动关联当前上下文的过滤器。
+    /// 名称中的 `.` 表示子指令，例如 `admin.ban` 可以通过 `admin ban` 或 `admin.ban` 调用，
+    /// 子指令只有在父指令的过滤器同样匹配时才可用。
+    /// `declaration` 为指令名称，后面可以跟随参数声明：`<name:type>` 为必选参数，
+    /// `[name:type=default]` 为可选参数，类型可以是 string、text、number、integer、
+    /// boolean、user、channel 与 image，其中 text 获取剩余的整行文本。
+    /// 调用时可以用引号包裹含空白的参数，消息中的提及、频道与图片元素可以直接作为对应类型的参数。
     ///
     /// # 例如
     ///
     /// ```ignore
-    /// ctx.command("ping")
-    ///    .description("Replies with pong")
-    ///    .action(|session, _args| Box::pin(async move {
-    ///        session.bot.send_message(&session.channel_id, "pong").await?;
-    ///        Ok(())
-    ///    }))
+    /// ctx.command("roll <count:integer> [sides:integer=6]")
+    ///    .option("verbose", "-v 显示每次的结果")
+    ///    .description("掷骰子")
+    ///    .action(|_session, args| async move {
+    ///        let count = args.get("count").and_then(|v| v.as_i64()).unwrap_or(1);
+    ///        let sides = args.get("sides").and_then(|v| v.as_i64()).unwrap_or(6);
+    ///        // 返回的消息会被自动发送
+    ///        Ok(format!("{}d{}", count, sides))
+    ///    })
     ///    .register()?;
     /// ```
-    pub fn command(&self, name: &str) -> CommandBuilder {
+    pub fn command(&self, declaration: &str) -> CommandBuilder {
         let registry_arc = {
-            let state_guard = self.shared_state.read().unwrap();
+            let state_guard = self.shared_state.read_recover();
             Arc::clone(&state_guard.command_registry)
         };
-        CommandBuilder::new(name.to_string(), self.current_filter.clone(), registry_arc)
+        CommandBuilder::new(
+            declaration.to_string(),
+            self.current_filter.clone(),
+            registry_arc,
+        )
+    }
+
+    /// 设置指令的权限解析器，用于确定发送者的权限等级与权限名称。
+    ///
+    /// 执行要求权限的指令时，解析前会获取发送者在群组中的角色（见 [`Session::resolve_roles`]）。
+    ///
+    /// # 例如
+    ///
+    /// ```ignore
+    /// ctx.set_permission_resolver(
+    ///     DefaultPermissionResolver::new()
+    ///         .user("onebot", "10000", Permissions::with_authority(4))
+    ///         .role("moderator", Permissions::with_authority(3).grant("admin.ban")),
+    /// );
+    /// ```
+    pub fn set_permission_resolver<R>(&self, resolver: R)
+    where
+        R: PermissionResolver + 'static,
+    {
+        self.shared_state.write_recover().permission.resolver = Arc::new(resolver);
+    }
+
+    /// 设置群组成员角色的缓存时间，默认为 5 分钟。
+    pub fn set_role_cache_ttl(&self, ttl: Duration) {
+        self.shared_state.write_recover().member_roles.ttl = ttl;
+    }
+
+    /// 设置指令冷却与调用次数记录的存储，默认保存在内存中。
+    pub fn set_usage_store<S>(&self, store: S)
+    where
+        S: UsageStore + 'static,
+    {
+        self.shared_state.write_recover().usage = UsageConfig::new(Arc::new(store));
+    }
+
+    /// 设置未知指令的处理方式，默认回复相近的指令名称。
+    pub fn set_suggestion_mode(&self, mode: SuggestionMode) {
+        self.shared_state.write_recover().suggestion.mode = mode;
+    }
+
+    /// 设置全局的指令前缀，`App::run` 会使用配置文件中的 `basic.prefix` 调用此方法。
+    pub fn set_prefixes<S: AsRef<str>>(&self, prefixes: &[S]) {
+        self.shared_state.write_recover().prefix.prefixes =
+            prefixes.iter().map(|p| p.as_ref().to_string()).collect();
+    }
+
+    /// 设置机器人的昵称，以昵称开头的消息无需前缀即可触发指令。
+    pub fn set_nicknames<S: AsRef<str>>(&self, nicknames: &[S]) {
+        self.shared_state.write_recover().prefix.nicknames =
+            nicknames.iter().map(|n| n.as_ref().to_string()).collect();
+    }
+
+    /// 获取群组单独设置的指令前缀。
+    pub fn guild_prefixes(&self, platform: &str, guild_id: &str) -> Option<Vec<String>> {
+        self.shared_state
+            .read_recover()
+            .prefix
+            .guild_prefixes(platform, guild_id)
+            .map(<[String]>::to_vec)
+    }
+
+    /// 为群组单独设置指令前缀，覆盖全局前缀，可以在运行时随时修改。
+    pub fn set_guild_prefixes<S: AsRef<str>>(
+        &self,
+        platform: &str,
+        guild_id: &str,
+        prefixes: &[S],
+    ) {
+        let prefixes = prefixes.iter().map(|p| p.as_ref().to_string()).collect();
+        self.shared_state
+            .write_recover()
+            .prefix
+            .set_guild_prefixes(platform, guild_id, prefixes);
+    }
+
+    /// 移除群组单独设置的指令前缀，恢复使用全局前缀。
+    pub fn remove_guild_prefixes(&self, platform: &str, guild_id: &str) -> bool {
+        self.shared_state
+            .write_recover()
+            .prefix
+            .remove_guild_prefixes(platform, guild_id)
+    }
+
+    /// 设置权限不足时默认回复的消息，指令可以通过 `CommandBuilder::denial_message` 单独设置。
+    pub fn set_denial_message(&self, message: &str) {
+        self.shared_state.write_recover().permission.denial_message = message.to_string();
+    }
+
+    /// 设置全局的指令执行时限，`None` 表示不限制，指令可以通过 `CommandBuilder::timeout` 单独设置。
+    pub fn set_command_timeout(&self, timeout: Option<Duration>) {
+        self.shared_state.write_recover().execution.timeout = timeout;
+    }
+
+    /// 设置指令执行超时时回复的消息。
+    pub fn set_timeout_message(&self, message: &str) {
+        self.shared_state.write_recover().execution.timeout_message = message.to_string();
+    }
+
+    /// 注册一个中间件。
+    ///
+    /// 中间件按注册顺序依次执行，只有过滤器匹配当前会话时才会被调用。
+    /// 调用 `next.run()` 继续执行后续中间件（最终为指令执行），不调用则截断消息处理。
+    ///
+    /// # 例如
+    ///
+    /// ```ignore
+    /// ctx.middleware(|session, next| async move {
+    ///     if session.content.contains("spam") {
+    ///         return Ok(()); // 截断，不再执行指令
+    ///     }
+    ///     next.run().await?;
+    ///     tracing::info!("消息 {} 处理完毕", session.message_id);
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn middleware<F, Fut>(&self, callback: F) -> MiddlewareHandle
+    where
+        F: Fn(Arc<Session>, Next) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = FrameworkResult<()>> + Send + 'static,
+    {
+        let id = Uuid::new_v4();
+        let middleware = Arc::new(RegisteredMiddleware {
+            id,
+            filter: self.current_filter.clone(),
+            callback: Box::new(move |session, next| Box::pin(callback(session, next))),
+        });
+        let mut state = self.shared_state.write_recover();
+        state.add_middleware(middleware);
+        MiddlewareHandle {
+            id,
+            shared_state: Arc::clone(&self.shared_state),
+        }
     }
 
     fn register_listener_internal(
         &self,
         event_name: &str,
         action: ListenerAction,
+        options: ListenerOptions,
     ) -> ListenerHandle {
         let id = Uuid::new_v4(); // 生成唯一ID
         let listener = Arc::new(RegisteredListener {
             id,
+            options,
             filter: self.current_filter.clone(), // 监听器关联到当前上下文的过滤器副本
             action,
         });
-        let mut state = self.shared_state.write().unwrap(); // 获取共享状态的写锁
+        let mut state = self.shared_state.write_recover(); // 获取共享状态的写锁
         state.add_listener(event_name.to_string(), Arc::clone(&listener));
         ListenerHandle {
             id,
@@ -123,28 +413,75 @@ impl Context {
 
     // 注册普通监听器
     pub fn on<F>(&self, event_name: &str, callback: F) -> ListenerHandle
+    where
+        F: FnMut(Option<&Session>, &[Box<dyn Any + Send + Sync>]) + Send + Sync + 'static,
+    {
+        self.on_with(event_name, ListenerOptions::default(), callback)
+    }
+
+    // 以指定的优先级选项注册普通监听器
+    pub fn on_with<F>(
+        &self,
+        event_name: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> ListenerHandle
     where
         F: FnMut(Option<&Session>, &[Box<dyn Any + Send + Sync>]) + Send + Sync + 'static,
     {
         self.register_listener_internal(
             event_name,
             ListenerAction::On(Mutex::new(Box::new(callback))),
+            options,
         )
     }
 
     // 注册一次性监听器
     pub fn once<F>(&self, event_name: &str, callback: F) -> ListenerHandle
+    where
+        F: FnMut(Option<&Session>, &[Box<dyn Any + Send + Sync>]) + Send + Sync + 'static,
+    {
+        self.once_with(event_name, ListenerOptions::default(), callback)
+    }
+
+    // 以指定的优先级选项注册一次性监听器
+    pub fn once_with<F>(
+        &self,
+        event_name: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> ListenerHandle
     where
         F: FnMut(Option<&Session>, &[Box<dyn Any + Send + Sync>]) + Send + Sync + 'static,
     {
         self.register_listener_internal(
             event_name,
             ListenerAction::Once(Mutex::new(Some(Box::new(callback)))),
+            options,
         )
     }
 
     // 注册可熔断监听器
     pub fn bail<F>(&self, event_name: &str, callback: F) -> ListenerHandle
+    where
+        F: FnMut(
+                Option<&Session>,
+                &[Box<dyn Any + Send + Sync>],
+            ) -> Option<Box<dyn Any + Send + Sync>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.bail_with(event_name, ListenerOptions::default(), callback)
+    }
+
+    // 以指定的优先级选项注册可熔断监听器
+    pub fn bail_with<F>(
+        &self,
+        event_name: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> ListenerHandle
     where
         F: FnMut(
                 Option<&Session>,
@@ -157,102 +494,563 @@ impl Context {
         self.register_listener_internal(
             event_name,
             ListenerAction::Bail(Mutex::new(Box::new(callback))),
+            options,
         )
     }
 
-    /// 发射事件
-    /// # Arguments
+    // 注册异步普通监听器
+    pub fn on_async<F, Fut>(&self, event_name: &str, callback: F) -> ListenerHandle
+    where
+        F: Fn(Option<Arc<Session>>, EventArgs) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_async_with(event_name, ListenerOptions::default(), callback)
+    }
+
+    // 以指定的优先级选项注册异步普通监听器
+    pub fn on_async_with<F, Fut>(
+        &self,
+        event_name: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> ListenerHandle
+    where
+        F: Fn(Option<Arc<Session>>, EventArgs) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.register_listener_internal(
+            event_name,
+            ListenerAction::OnAsync(Box::new(move |session, args| {
+                Box::pin(callback(session, args))
+            })),
+            options,
+        )
+    }
+
+    // 注册异步一次性监听器
+    pub fn once_async<F, Fut>(&self, event_name: &str, callback: F) -> ListenerHandle
+    where
+        F: FnOnce(Option<Arc<Session>>, EventArgs) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.once_async_with(event_name, ListenerOptions::default(), callback)
+    }
+
+    // 以指定的优先级选项注册异步一次性监听器
+    pub fn once_async_with<F, Fut>(
+        &self,
+        event_name: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> ListenerHandle
+    where
+        F: FnOnce(Option<Arc<Session>>, EventArgs) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.register_listener_internal(
+            event_name,
+            ListenerAction::OnceAsync(Mutex::new(Some(Box::new(move |session, args| {
+                Box::pin(callback(session, args))
+            })))),
+            options,
+        )
+    }
+
+    // 注册异步可熔断监听器
+    pub fn bail_async<F, Fut>(&self, event_name: &str, callback: F) -> ListenerHandle
+    where
+        F: Fn(Option<Arc<Session>>, EventArgs) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<Box<dyn Any + Send + Sync>>> + Send + 'static,
+    {
+        self.bail_async_with(event_name, ListenerOptions::default(), callback)
+    }
+
+    // 以指定的优先级选项注册异步可熔断监听器
+    pub fn bail_async_with<F, Fut>(
+        &self,
+        event_name: &str,
+        options: ListenerOptions,
+        callback: F,
+    ) -> ListenerHandle
+    where
+        F: Fn(Option<Arc<Session>>, EventArgs) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<Box<dyn Any + Send + Sync>>> + Send + 'static,
+    {
+        self.register_listener_internal(
+            event_name,
+            ListenerAction::BailAsync(Box::new(move |session, args| {
+                Box::pin(callback(session, args))
+            })),
+            options,
+        )
+    }
+
+    /// 注册强类型事件监听器
     ///
-    /// `session_context`: 可选的会话上下文，用于过滤器匹配
-    /// `event_name`: 事件名称
-    /// `args`: 事件参数列表
+    /// 监听器只会在事件参数中携带了 `E` 类型的事件实例时被调用。
     ///
-    /// # Returns
-    /// 如果某个 bail 监听器熔断了，则返回其返回值
-    pub fn emit(
+    /// # 例如
+    ///
+    /// ```ignore
+    /// ctx.on_event::<GuildMemberAdded, _>(|session, ev| {
+    ///     tracing::info!("{} 加入了群组 {}", ev.user.id, ev.guild.id);
+    /// });
+    /// ```
+    pub fn on_event<E, F>(&self, mut callback: F) -> ListenerHandle
+    where
+        E: Event,
+        F: FnMut(Option<&Session>, &E) + Send + Sync + 'static,
+    {
+        self.on(E::NAME, move |session, args| {
+            match args.first().and_then(|arg| arg.downcast_ref::<E>()) {
+                Some(event) => callback(session, event),
+                None => tracing::warn!("事件 {} 的参数类型不匹配，已跳过强类型监听器", E::NAME),
+            }
+        })
+    }
+
+    /// 注册异步强类型事件监听器
+    pub fn on_event_async<E, F, Fut>(&self, callback: F) -> ListenerHandle
+    where
+        E: Event + Clone,
+        F: Fn(Option<Arc<Session>>, E) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.on_async(E::NAME, move |session, args| {
+            let event = args
+                .first()
+                .and_then(|arg| arg.downcast_ref::<E>())
+                .cloned();
+            let callback = Arc::clone(&callback);
+            async move {
+                match event {
+                    Some(event) => callback(session, event).await,
+                    None => tracing::warn!("事件 {} 的参数类型不匹配，已跳过强类型监听器", E::NAME),
+                }
+            }
+        })
+    }
+
+    /// 注册指令执行前的钩子。
+    ///
+    /// 钩子在参数解析、检查与调用限制都通过之后，指令执行之前按注册顺序运行，
+    /// 可以用于审计或确认提示。返回 `Some(message)` 时指令不会执行，`message` 会回复给用户。
+    ///
+    /// # 例如
+    ///
+    /// ```ignore
+    /// ctx.before_execute(|session, event| async move {
+    ///     tracing::info!("{} 调用了指令 {}", session.user_id, event.command.name);
+    ///     None
+    /// });
+    /// ```
+    pub fn before_execute<F, Fut>(&self, callback: F) -> ListenerHandle
+    where
+        F: Fn(Arc<Session>, CommandBeforeExecute) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<String>> + Send + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.bail_async(CommandBeforeExecute::NAME, move |session, args| {
+            let event = args
+                .first()
+                .and_then(|arg| arg.downcast_ref::<CommandBeforeExecute>())
+                .cloned();
+            let callback = Arc::clone(&callback);
+            async move {
+                let (Some(session), Some(event)) = (session, event) else {
+                    return None;
+                };
+                callback(session, event)
+                    .await
+                    .map(|reply| Box::new(reply) as Box<dyn Any + Send + Sync>)
+            }
+        })
+    }
+
+    /// 注册指令执行后的钩子，无论指令成功与否都会运行，可以用于审计或统计。
+    pub fn after_execute<F, Fut>(&self, callback: F) -> ListenerHandle
+    where
+        F: Fn(Arc<Session>, CommandAfterExecute) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let callback = Arc::new(callback);
+        self.on_event_async(move |session, event: CommandAfterExecute| {
+            let callback = Arc::clone(&callback);
+            async move {
+                if let Some(session) = session {
+                    callback(session, event).await;
+                }
+            }
+        })
+    }
+
+    /// 发射强类型事件
+    pub fn emit_event<E: Event>(
         &self,
-        event_name: &str,
         session_context: Option<&Session>,
-        args: &[Box<dyn Any + Send + Sync>],
+        event: E,
+    ) -> Option<Box<dyn Any + Send + Sync>> {
+        self.emit(E::NAME, session_context, &[Box::new(event)])
+    }
+
+    /// 异步发射强类型事件
+    pub async fn emit_event_async<E: Event>(
+        &self,
+        session_context: Option<Arc<Session>>,
+        event: E,
+        mode: EmitMode,
     ) -> Option<Box<dyn Any + Send + Sync>> {
-        let state_read_guard = self.shared_state.read().unwrap(); // 获取读锁
+        self.emit_async(E::NAME, session_context, vec![Box::new(event)], mode)
+            .await
+    }
+
+    // 获取某个事件下过滤器匹配当前会话的监听器（按注册顺序）
+    fn matching_listeners(
+        &self,
+        event_name: &str,
+        session_context: Option<&Session>,
+    ) -> Vec<Arc<RegisteredListener>> {
+        let state_read_guard = self.shared_state.read_recover(); // 获取读锁
         let listeners_for_event_arcs = match state_read_guard.listeners_by_event.get(event_name) {
             Some(listeners) => listeners.clone(), // 克隆 Arc 列表，以便在锁外操作
-            None => return None,                  // 没有此事件的监听器
+            None => return Vec::new(),            // 没有此事件的监听器
         };
         drop(state_read_guard); // 释放读锁，避免长时间持有
 
-        let mut ids_of_once_listeners_fired: Vec<ListenerId> = Vec::new(); // 存储已触发的 Once 监听器ID
-
-        for listener_arc in listeners_for_event_arcs {
-            //  检查上下文过滤器是否匹配
-            let should_run = match session_context {
+        listeners_for_event_arcs
+            .into_iter()
+            .filter(|listener_arc| match session_context {
                 Some(s_ctx) => listener_arc.filter.matches_session(s_ctx),
                 None => listener_arc.filter.matches_generic(), // 对于无会话的通用事件
-            };
+            })
+            .collect()
+    }
 
-            if !should_run {
-                continue; // 过滤器不匹配，跳过此监听器
-            }
+    // 某个事件下是否有按角色过滤的监听器
+    fn listeners_need_roles(&self, event_name: &str) -> bool {
+        self.shared_state
+            .read_recover()
+            .listeners_by_event
+            .get(event_name)
+            .is_some_and(|listeners| listeners.iter().any(|l| l.filter.needs_roles()))
+    }
+
+    // 统一清理已触发的 Once 监听器
+    fn remove_fired_once_listeners(&self, ids: &[ListenerId]) {
+        if ids.is_empty() {
+            return;
+        }
+        let mut state_write_guard = self.shared_state.write_recover();
+        for id in ids {
+            state_write_guard.remove_listener(*id);
+        }
+    }
+
+    /// 上报框架内部错误
+    ///
+    /// 错误会记录到日志，并以 `InternalError` 为参数发射 `internal/error` 事件
+    /// （只会执行同步监听器）。`internal/error` 监听器自身的错误只记录日志，避免递归。
+    pub fn report_error(&self, session_context: Option<&Session>, error: InternalError) {
+        tracing::error!("{:?} 执行失败: {}", error.source, error.message);
+        if matches!(&error.source, ErrorSource::Listener { event } if event == InternalError::NAME)
+        {
+            return;
+        }
+        self.emit_event(session_context, error);
+    }
 
-            //  根据监听器类型执行回调
-            match &listener_arc.action {
-                ListenerAction::On(cb_mutex) => {
-                    // 对于 On 监听器，获取其回调的锁并执行
-                    // 使用 try_lock 更好，以防死锁（如果回调内部又 emit 同步事件）
-                    if let Ok(mut cb_guard) = cb_mutex.try_lock() {
+    // 上报监听器执行过程中的 panic
+    fn report_listener_panic(
+        &self,
+        event_name: &str,
+        session_context: Option<&Session>,
+        listener_id: ListenerId,
+        payload: Box<dyn Any + Send>,
+    ) {
+        self.report_error(
+            session_context,
+            InternalError {
+                source: ErrorSource::Listener {
+                    event: event_name.to_string(),
+                },
+                message: format!("监听器 {} panic: {}", listener_id, panic_message(&*payload)),
+                panicked: true,
+            },
+        );
+    }
+
+    // 执行同步监听器的回调，返回 bail 监听器的熔断值。
+    // 回调 panic 时会被捕获并上报，视为未熔断。
+    fn invoke_sync_listener(
+        &self,
+        event_name: &str,
+        listener_arc: &RegisteredListener,
+        session_context: Option<&Session>,
+        args: &[Box<dyn Any + Send + Sync>],
+        ids_of_once_listeners_fired: &mut Vec<ListenerId>,
+    ) -> Option<Box<dyn Any + Send + Sync>> {
+        // 在持有回调锁期间捕获 panic，因此回调锁不会中毒
+        let result = match &listener_arc.action {
+            ListenerAction::On(cb_mutex) => {
+                // 对于 On 监听器，获取其回调的锁并执行
+                // 使用 try_lock 更好，以防死锁（如果回调内部又 emit 同步事件）
+                if let Some(mut cb_guard) = cb_mutex.try_lock_recover() {
+                    panic::catch_unwind(AssertUnwindSafe(|| {
                         (*cb_guard)(session_context, args);
-                    } else {
-                        // 处理无法获取锁的情况，例如打印警告
-                        eprintln!(
-                            "[事件系统警告] 无法获取 On 监听器 {} 的锁，可能存在重入或竞争。",
-                            listener_arc.id
-                        );
-                    }
+                        None
+                    }))
+                } else {
+                    // 处理无法获取锁的情况，例如打印警告
+                    eprintln!(
+                        "[事件系统警告] 无法获取 On 监听器 {} 的锁，可能存在重入或竞争。",
+                        listener_arc.id
+                    );
+                    Ok(None)
                 }
-                ListenerAction::Once(cb_mutex_opt) => {
-                    let mut opt_cb_guard = cb_mutex_opt.lock().unwrap();
-                    if let Some(mut cb) = opt_cb_guard.take() {
-                        // 尝试取出回调
-                        // 成功取出，表示这是第一次执行
-                        drop(opt_cb_guard); // 在调用回调前释放锁
+            }
+            ListenerAction::Once(cb_mutex_opt) => {
+                let mut opt_cb_guard = cb_mutex_opt.lock_recover();
+                if let Some(mut cb) = opt_cb_guard.take() {
+                    // 尝试取出回调
+                    // 成功取出，表示这是第一次执行
+                    drop(opt_cb_guard); // 在调用回调前释放锁
+                    ids_of_once_listeners_fired.push(listener_arc.id); // 记录此ID，稍后移除
+                    panic::catch_unwind(AssertUnwindSafe(|| {
                         cb(session_context, args);
-                        ids_of_once_listeners_fired.push(listener_arc.id); // 记录此ID，稍后移除
-                    }
+                        None
+                    }))
+                } else {
                     // 如果 opt_cb_guard.take() 返回 None，说明回调已被取走，不再执行
+                    Ok(None)
                 }
-                ListenerAction::Bail(cb_mutex) => {
-                    if let Ok(mut cb_guard) = cb_mutex.try_lock() {
-                        let bail_result = (*cb_guard)(session_context, args);
-                        if bail_result.is_some() {
-                            // Bail 监听器返回了 Some 值，表示熔断
-                            // 在返回前，清理掉在此 Bail 之前已触发的 Once 监听器
-                            if !ids_of_once_listeners_fired.is_empty() {
-                                let mut state_write_guard = self.shared_state.write().unwrap();
-                                for id in &ids_of_once_listeners_fired {
-                                    state_write_guard.remove_listener(*id);
-                                }
-                            }
-                            return bail_result; // 立即返回熔断结果
-                        }
-                    } else {
-                        eprintln!(
-                            "[事件系统警告] 无法获取 Bail 监听器 {} 的锁，可能存在重入或竞争。",
-                            listener_arc.id
-                        );
-                    }
+            }
+            ListenerAction::Bail(cb_mutex) => {
+                if let Some(mut cb_guard) = cb_mutex.try_lock_recover() {
+                    panic::catch_unwind(AssertUnwindSafe(|| (*cb_guard)(session_context, args)))
+                } else {
+                    eprintln!(
+                        "[事件系统警告] 无法获取 Bail 监听器 {} 的锁，可能存在重入或竞争。",
+                        listener_arc.id
+                    );
+                    Ok(None)
                 }
             }
+            // 异步监听器只会由 emit_async 触发
+            ListenerAction::OnAsync(_)
+            | ListenerAction::OnceAsync(_)
+            | ListenerAction::BailAsync(_) => {
+                tracing::trace!("同步 emit 跳过异步监听器 {}", listener_arc.id);
+                Ok(None)
+            }
+        };
+        result.unwrap_or_else(|payload| {
+            self.report_listener_panic(event_name, session_context, listener_arc.id, payload);
+            None
+        })
+    }
+
+    // 执行异步监听器的回调，回调或其 Future panic 时会被捕获并上报，视为未熔断
+    async fn invoke_async_listener<F, Fut>(
+        &self,
+        event_name: &str,
+        listener_id: ListenerId,
+        session_context: Option<&Session>,
+        invoke: F,
+    ) -> Option<Box<dyn Any + Send + Sync>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Option<Box<dyn Any + Send + Sync>>>,
+    {
+        // 在 async 块中调用回调，使构造 Future 时的 panic 也能被捕获
+        match AssertUnwindSafe(async move { invoke().await })
+            .catch_unwind()
+            .await
+        {
+            Ok(bail_result) => bail_result,
+            Err(payload) => {
+                self.report_listener_panic(event_name, session_context, listener_id, payload);
+                None
+            }
         }
+    }
 
-        // 循环结束后，统一清理所有已触发的 Once 监听器
-        if !ids_of_once_listeners_fired.is_empty() {
-            let mut state_write_guard = self.shared_state.write().unwrap();
-            for id in ids_of_once_listeners_fired {
-                state_write_guard.remove_listener(id);
+    // 为异步监听器构建带 panic 隔离的调用 Future；同步监听器与已被取走回调的 once 监听器返回 None
+    fn start_async_listener<'a>(
+        &'a self,
+        event_name: &'a str,
+        listener_arc: &'a RegisteredListener,
+        session_context: &'a Option<Arc<Session>>,
+        args: &EventArgs,
+        ids_of_once_listeners_fired: &mut Vec<ListenerId>,
+    ) -> Option<GuardedFuture<'a>> {
+        let session = session_context.clone();
+        let args = Arc::clone(args);
+        match &listener_arc.action {
+            ListenerAction::OnAsync(cb) => Some(Box::pin(self.invoke_async_listener(
+                event_name,
+                listener_arc.id,
+                session_context.as_deref(),
+                move || async move {
+                    cb(session, args).await;
+                    None
+                },
+            ))),
+            ListenerAction::OnceAsync(cb_mutex_opt) => {
+                let cb = cb_mutex_opt.lock_recover().take()?;
+                ids_of_once_listeners_fired.push(listener_arc.id);
+                Some(Box::pin(self.invoke_async_listener(
+                    event_name,
+                    listener_arc.id,
+                    session_context.as_deref(),
+                    move || async move {
+                        cb(session, args).await;
+                        None
+                    },
+                )))
+            }
+            ListenerAction::BailAsync(cb) => Some(Box::pin(self.invoke_async_listener(
+                event_name,
+                listener_arc.id,
+                session_context.as_deref(),
+                move || cb(session, args),
+            ))),
+            _ => None,
+        }
+    }
+
+    /// 发射事件
+    ///
+    /// 只会执行同步监听器，异步监听器需通过 `emit_async` 触发。
+    /// 按角色过滤的监听器只使用会话中已有的角色，不会获取角色。
+    /// 监听器 panic 时会被捕获并通过 `internal/error` 事件上报，不影响其余监听器。
+    ///
+    /// # Arguments
+    ///
+    /// `session_context`: 可选的会话上下文，用于过滤器匹配
+    /// `event_name`: 事件名称
+    /// `args`: 事件参数列表
+    ///
+    /// # Returns
+    /// 如果某个 bail 监听器熔断了，则返回其返回值
+    pub fn emit(
+        &self,
+        event_name: &str,
+        session_context: Option<&Session>,
+        args: &[Box<dyn Any + Send + Sync>],
+    ) -> Option<Box<dyn Any + Send + Sync>> {
+        let mut ids_of_once_listeners_fired: Vec<ListenerId> = Vec::new(); // 存储已触发的 Once 监听器ID
+
+        for listener_arc in self.matching_listeners(event_name, session_context) {
+            let bail_result = self.invoke_sync_listener(
+                event_name,
+                &listener_arc,
+                session_context,
+                args,
+                &mut ids_of_once_listeners_fired,
+            );
+            if bail_result.is_some() {
+                // Bail 监听器返回了 Some 值，表示熔断
+                // 在返回前，清理掉在此 Bail 之前已触发的 Once 监听器
+                self.remove_fired_once_listeners(&ids_of_once_listeners_fired);
+                return bail_result; // 立即返回熔断结果
             }
         }
 
+        // 循环结束后，统一清理所有已触发的 Once 监听器
+        self.remove_fired_once_listeners(&ids_of_once_listeners_fired);
+
         None // 没有监听器熔断
     }
+
+    /// 异步发射事件
+    ///
+    /// 同步与异步监听器都会被执行。
+    /// 监听器 panic 时会被捕获并通过 `internal/error` 事件上报，不影响其余监听器。
+    ///
+    /// # Arguments
+    ///
+    /// `event_name`: 事件名称
+    /// `session_context`: 可选的会话上下文，用于过滤器匹配
+    /// `args`: 事件参数列表
+    /// `mode`: 监听器的执行方式。`Serial` 按顺序逐个等待，遇到 bail 熔断立即返回；
+    /// `Parallel` 并发执行所有监听器，不会中途熔断。
+    ///
+    /// # Returns
+    /// 按监听器顺序，第一个熔断的 bail 监听器的返回值
+    pub async fn emit_async(
+        &self,
+        event_name: &str,
+        session_context: Option<Arc<Session>>,
+        args: Vec<Box<dyn Any + Send + Sync>>,
+        mode: EmitMode,
+    ) -> Option<Box<dyn Any + Send + Sync>> {
+        // 有监听器按角色过滤时，匹配前先获取发送者的角色
+        if let Some(session) = &session_context
+            && self.listeners_need_roles(event_name)
+        {
+            session.resolve_roles().await;
+        }
+        let listeners = self.matching_listeners(event_name, session_context.as_deref());
+        let args: EventArgs = Arc::new(args);
+        let mut ids_of_once_listeners_fired: Vec<ListenerId> = Vec::new();
+        match mode {
+            EmitMode::Serial => {
+                for listener_arc in &listeners {
+                    let invoke = self.start_async_listener(
+                        event_name,
+                        listener_arc,
+                        &session_context,
+                        &args,
+                        &mut ids_of_once_listeners_fired,
+                    );
+                    let bail_result = match invoke {
+                        Some(fut) => fut.await,
+                        None => self.invoke_sync_listener(
+                            event_name,
+                            listener_arc,
+                            session_context.as_deref(),
+                            &args,
+                            &mut ids_of_once_listeners_fired,
+                        ),
+                    };
+                    if bail_result.is_some() {
+                        self.remove_fired_once_listeners(&ids_of_once_listeners_fired);
+                        return bail_result;
+                    }
+                }
+                self.remove_fired_once_listeners(&ids_of_once_listeners_fired);
+                None
+            }
+            EmitMode::Parallel => {
+                let mut futures: Vec<GuardedFuture> = Vec::new();
+                for listener_arc in &listeners {
+                    let invoke = self.start_async_listener(
+                        event_name,
+                        listener_arc,
+                        &session_context,
+                        &args,
+                        &mut ids_of_once_listeners_fired,
+                    );
+                    match invoke {
+                        Some(fut) => futures.push(fut),
+                        None => {
+                            // 同步监听器立即执行，其结果按顺序参与熔断值的选取
+                            let bail_result = self.invoke_sync_listener(
+                                event_name,
+                                listener_arc,
+                                session_context.as_deref(),
+                                &args,
+                                &mut ids_of_once_listeners_fired,
+                            );
+                            futures.push(Box::pin(std::future::ready(bail_result)));
+                        }
+                    }
+                }
+                // once 监听器在启动时即已取出回调，可以先行清理
+                self.remove_fired_once_listeners(&ids_of_once_listeners_fired);
+                join_all(futures).await.into_iter().flatten().next()
+            }
+        }
+    }
 }
@@ -1,12 +1,112 @@
 use crate::session::Session;
 use std::collections::HashSet;
+use std::fmt::Debug;
+use std::sync::Arc;
 
+/// 自定义的会话判定函数
+#[derive(Clone)]
+pub struct FilterPredicate(pub Arc<dyn Fn(&Session) -> bool + Send + Sync>);
+
+impl Debug for FilterPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FilterPredicate(Fn(&Session) -> bool)")
+    }
+}
+
+impl PartialEq for FilterPredicate {
+    // 闭包无法比较内容，只有指向同一个闭包时才视为相等
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for FilterPredicate {}
+
+/// 过滤器表达式树中的组合节点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterCondition {
+    /// 所有子过滤器都匹配（交集）
+    All(Vec<ContextFilter>),
+    /// 任一子过滤器匹配（并集）
+    Any(Vec<ContextFilter>),
+    /// 子过滤器不匹配（排除）
+    Not(Box<ContextFilter>),
+    /// 自定义判定函数
+    Predicate(FilterPredicate),
+}
+
+impl FilterCondition {
+    fn matches_session(&self, session: &Session) -> bool {
+        match self {
+            FilterCondition::All(filters) => filters.iter().all(|f| f.matches_session(session)),
+            FilterCondition::Any(filters) => filters.iter().any(|f| f.matches_session(session)),
+            FilterCondition::Not(filter) => !filter.matches_session(session),
+            FilterCondition::Predicate(predicate) => (predicate.0)(session),
+        }
+    }
+
+    fn needs_roles(&self) -> bool {
+        match self {
+            FilterCondition::All(filters) | FilterCondition::Any(filters) => {
+                filters.iter().any(ContextFilter::needs_roles)
+            }
+            FilterCondition::Not(filter) => filter.needs_roles(),
+            FilterCondition::Predicate(_) => false,
+        }
+    }
+
+    fn fingerprint(&self) -> String {
+        let join = |filters: &[ContextFilter]| {
+            filters
+                .iter()
+                .map(ContextFilter::fingerprint)
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        match self {
+            FilterCondition::All(filters) => format!("all({})", join(filters)),
+            FilterCondition::Any(filters) => format!("any({})", join(filters)),
+            FilterCondition::Not(filter) => format!("not({})", filter.fingerprint()),
+            // 闭包没有可比较的内容，只记录存在判定函数
+            FilterCondition::Predicate(_) => "predicate".to_string(),
+        }
+    }
+
+    fn matches_generic(&self) -> bool {
+        match self {
+            FilterCondition::All(filters) => filters.iter().all(|f| f.matches_generic()),
+            FilterCondition::Any(filters) => filters.iter().any(|f| f.matches_generic()),
+            FilterCondition::Not(filter) => !filter.matches_generic(),
+            // 判定函数依赖会话数据，无会话时视为不匹配
+            FilterCondition::Predicate(_) => false,
+        }
+    }
+
+    // 能否确定此节点与另一过滤器不会同时匹配同一会话
+    fn is_disjoint(&self, other: &ContextFilter) -> bool {
+        match self {
+            FilterCondition::All(filters) => filters.iter().any(|f| f.is_disjoint(other)),
+            FilterCondition::Any(filters) => filters.iter().all(|f| f.is_disjoint(other)),
+            // 排除与判定函数无法静态分析
+            FilterCondition::Not(_) | FilterCondition::Predicate(_) => false,
+        }
+    }
+}
+
+/// 上下文过滤器
+///
+/// 各字段之间、以及与 `conditions` 中的每个节点之间均为“与”关系；
+/// 并集、排除等组合通过 `conditions` 中嵌套的子过滤器表达。
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ContextFilter {
     pub user_ids: Option<HashSet<String>>,
     pub guild_ids: Option<HashSet<String>>,
     pub platforms: Option<HashSet<String>>,
     pub is_private: Option<bool>, // true: 仅私聊, false: 仅群聊, None: 两者皆可
+    pub channel_ids: Option<HashSet<String>>,
+    pub self_ids: Option<HashSet<String>>, // 接收会话的机器人账号
+    pub role_ids: Option<HashSet<String>>, // 发送者拥有其中任一角色即匹配
+    pub conditions: Vec<FilterCondition>,
 }
 
 impl ContextFilter {
@@ -31,6 +131,24 @@ impl ContextFilter {
             .insert(platform.to_string());
         self
     }
+    pub fn channel(mut self, channel_id: &str) -> Self {
+        self.channel_ids
+            .get_or_insert_with(HashSet::new)
+            .insert(channel_id.to_string());
+        self
+    }
+    pub fn self_id(mut self, self_id: &str) -> Self {
+        self.self_ids
+            .get_or_insert_with(HashSet::new)
+            .insert(self_id.to_string());
+        self
+    }
+    pub fn role(mut self, role_id: &str) -> Self {
+        self.role_ids
+            .get_or_insert_with(HashSet::new)
+            .insert(role_id.to_string());
+        self
+    }
     pub fn private(mut self) -> Self {
         self.is_private = Some(true);
         self
@@ -40,12 +158,80 @@ impl ContextFilter {
         self
     }
 
+    // --- 组合方法 ---
+    /// 匹配当前过滤器或另一过滤器的会话
+    pub fn union(self, other: ContextFilter) -> Self {
+        ContextFilter::new().condition(FilterCondition::Any(vec![self, other]))
+    }
+    /// 同时匹配当前过滤器与另一过滤器的会话
+    pub fn intersect(self, other: ContextFilter) -> Self {
+        self.condition(FilterCondition::All(vec![other]))
+    }
+    /// 匹配当前过滤器但不匹配另一过滤器的会话
+    pub fn exclude(self, other: ContextFilter) -> Self {
+        self.condition(FilterCondition::Not(Box::new(other)))
+    }
+    /// 追加一个自定义判定函数
+    pub fn predicate<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&Session) -> bool + Send + Sync + 'static,
+    {
+        self.condition(FilterCondition::Predicate(FilterPredicate(Arc::new(
+            predicate,
+        ))))
+    }
+    fn condition(mut self, condition: FilterCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// 两个过滤器是否一定不会同时匹配同一会话。
+    ///
+    /// 只比较用户、群组、平台、私聊、频道与机器人账号等可以静态确定的条件，
+    /// 无法确定时（例如包含排除或判定函数）视为可能重叠，返回 `false`。
+    pub fn is_disjoint(&self, other: &ContextFilter) -> bool {
+        fn sets_disjoint(a: &Option<HashSet<String>>, b: &Option<HashSet<String>>) -> bool {
+            matches!((a, b), (Some(a), Some(b)) if a.is_disjoint(b))
+        }
+        // 会话可以同时拥有多个角色，角色不同的过滤器仍可能匹配同一会话
+        sets_disjoint(&self.user_ids, &other.user_ids)
+            || sets_disjoint(&self.guild_ids, &other.guild_ids)
+            || sets_disjoint(&self.platforms, &other.platforms)
+            || sets_disjoint(&self.channel_ids, &other.channel_ids)
+            || sets_disjoint(&self.self_ids, &other.self_ids)
+            || matches!((self.is_private, other.is_private), (Some(a), Some(b)) if a != b)
+            || self.conditions.iter().any(|c| c.is_disjoint(other))
+            || other.conditions.iter().any(|c| c.is_disjoint(self))
+    }
+
+    /// 此过滤器是否一定匹配另一过滤器能匹配的全部会话。
+    ///
+    /// 此过滤器的每个条件都须在另一过滤器中以相同或更严格的形式出现，
+    /// 无法确定时返回 `false`。
+    pub fn covers(&self, other: &ContextFilter) -> bool {
+        fn set_covers(a: &Option<HashSet<String>>, b: &Option<HashSet<String>>) -> bool {
+            match (a, b) {
+                (None, _) => true,
+                (Some(a), Some(b)) => b.is_subset(a),
+                (Some(_), None) => false,
+            }
+        }
+        set_covers(&self.user_ids, &other.user_ids)
+            && set_covers(&self.guild_ids, &other.guild_ids)
+            && set_covers(&self.platforms, &other.platforms)
+            && set_covers(&self.channel_ids, &other.channel_ids)
+            && set_covers(&self.self_ids, &other.self_ids)
+            && set_covers(&self.role_ids, &other.role_ids)
+            && (self.is_private.is_none() || self.is_private == other.is_private)
+            && self.conditions.iter().all(|c| other.conditions.contains(c))
+    }
+
     // 检查过滤器是否匹配给定的 Session
     pub fn matches_session(&self, session: &Session) -> bool {
-        if let Some(users) = &self.user_ids {
-            if !users.contains(&session.user_id) {
-                return false;
-            }
+        if let Some(users) = &self.user_ids
+            && !users.contains(&session.user_id)
+        {
+            return false;
         }
         if let Some(guilds) = &self.guild_ids {
             let gid = &session.guild_id;
@@ -53,18 +239,67 @@ impl ContextFilter {
                 return false; // 群聊ID不匹配
             }
         }
-        if let Some(platforms) = &self.platforms {
-            if !platforms.contains(&session.platform) {
-                return false;
-            }
+        if let Some(platforms) = &self.platforms
+            && !platforms.contains(&session.platform)
+        {
+            return false;
         }
-        if let Some(is_private_filter) = self.is_private {
-            if is_private_filter != session.is_direct {
+        if let Some(is_private_filter) = self.is_private
+            && is_private_filter != session.is_direct
+        {
+            return false;
+        }
+        if let Some(channels) = &self.channel_ids
+            && !channels.contains(&session.channel_id)
+        {
+            return false;
+        }
+        if let Some(self_ids) = &self.self_ids
+            && !self_ids.contains(&session.self_id)
+        {
+            return false;
+        }
+        if let Some(roles) = &self.role_ids {
+            // 未获取到角色信息的会话不匹配角色过滤器
+            let has_role = session
+                .roles()
+                .is_some_and(|session_roles| session_roles.iter().any(|r| roles.contains(r)));
+            if !has_role {
                 return false;
             }
         }
-        true
+        self.conditions.iter().all(|c| c.matches_session(session))
+    }
+    /// 过滤器是否按发送者的角色过滤，匹配前需要先获取会话的角色（见 `Session::resolve_roles`）
+    pub fn needs_roles(&self) -> bool {
+        self.role_ids.is_some() || self.conditions.iter().any(FilterCondition::needs_roles)
     }
+    /// 过滤器的稳定文本表示，集合按字典序排列，同一过滤器在每次启动时都得到相同结果。
+    ///
+    /// 根上下文的过滤器返回空字符串。
+    pub fn fingerprint(&self) -> String {
+        fn set(name: &str, values: &Option<HashSet<String>>) -> Option<String> {
+            let mut values: Vec<_> = values.as_ref()?.iter().map(String::as_str).collect();
+            values.sort_unstable();
+            Some(format!("{}=[{}]", name, values.join("|")))
+        }
+        let mut parts: Vec<String> = [
+            set("user", &self.user_ids),
+            set("guild", &self.guild_ids),
+            set("platform", &self.platforms),
+            self.is_private
+                .map(|private| format!("private={}", private)),
+            set("channel", &self.channel_ids),
+            set("self", &self.self_ids),
+            set("role", &self.role_ids),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        parts.extend(self.conditions.iter().map(FilterCondition::fingerprint));
+        parts.join(";")
+    }
+
     // 检查过滤器是否匹配没有 Session 上下文的通用事件
     pub fn matches_generic(&self) -> bool {
         // 如果过滤器指定了用户/群组/私聊等会话相关的属性，
@@ -74,6 +309,10 @@ impl ContextFilter {
         self.user_ids.is_none() &&
         self.guild_ids.is_none() &&
         self.platforms.is_none() && // 平台相关的过滤器可能对通用事件依然有意义
-        self.is_private.is_none()
+        self.is_private.is_none() &&
+        self.channel_ids.is_none() &&
+        self.self_ids.is_none() &&
+        self.role_ids.is_none() &&
+        self.conditions.iter().all(|c| c.matches_generic())
     }
 }
@@ -1,9 +1,12 @@
 use std::any::Any;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex, RwLock};
 use uuid::Uuid;
 
 use super::{filter::ContextFilter, state::EventSystemSharedState};
 use crate::session::Session;
+use crate::sync::RwLockExt;
 
 pub type ListenerId = Uuid;
 
@@ -18,6 +21,18 @@ pub type BailCallback = Box<
         + Sync,
 >;
 
+// 异步监听器的事件参数在多个监听器之间共享
+pub type EventArgs = Arc<Vec<Box<dyn Any + Send + Sync>>>;
+pub type ListenerFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+pub type BailFuture = Pin<Box<dyn Future<Output = Option<Box<dyn Any + Send + Sync>>> + Send>>;
+// 异步回调通过共享所有权接收会话与参数，因此可以跨越 await
+pub type AsyncListenerCallback =
+    Box<dyn Fn(Option<Arc<Session>>, EventArgs) -> ListenerFuture + Send + Sync>;
+pub type AsyncOnceCallback =
+    Box<dyn FnOnce(Option<Arc<Session>>, EventArgs) -> ListenerFuture + Send>;
+pub type AsyncBailCallback =
+    Box<dyn Fn(Option<Arc<Session>>, EventArgs) -> BailFuture + Send + Sync>;
+
 // ListenerAction 枚举，用于存储不同类型的回调逻辑
 // 注意：FnMut 回调如果捕获了可变状态，并且监听器被 Arc 共享，
 // 则需要 Mutex 来保证线程安全。
@@ -25,12 +40,49 @@ pub enum ListenerAction {
     On(Mutex<ListenerCallback>),
     Once(Mutex<Option<ListenerCallback>>), // Option 用于“取出”回调，实现一次性
     Bail(Mutex<BailCallback>),             // Bail监听器是持久的，除非显式移除
+    OnAsync(AsyncListenerCallback),
+    OnceAsync(Mutex<Option<AsyncOnceCallback>>),
+    BailAsync(AsyncBailCallback),
+}
+
+/// `emit_async` 执行监听器的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmitMode {
+    /// 按顺序逐个等待，bail 监听器可以熔断
+    #[default]
+    Serial,
+    /// 并发执行所有监听器
+    Parallel,
+}
+
+/// 注册监听器时的排序选项
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ListenerOptions {
+    /// 优先级，数值越大越先执行，默认为 0
+    pub priority: i32,
+    /// 为 true 时排在同优先级监听器之前，否则排在之后
+    pub prepend: bool,
+}
+
+impl ListenerOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+    pub fn prepend(mut self) -> Self {
+        self.prepend = true;
+        self
+    }
 }
 
 pub struct RegisteredListener {
     pub id: ListenerId,
-    pub filter: ContextFilter,  // 注册时此监听器关联的上下文过滤器
-    pub action: ListenerAction, // 监听器具体行为 (On, Once, Bail 及其回调)
+    pub options: ListenerOptions, // 决定监听器在事件列表中的位置
+    pub filter: ContextFilter,    // 注册时此监听器关联的上下文过滤器
+    pub action: ListenerAction,   // 监听器具体行为 (On, Once, Bail 及其回调)
 }
 
 // 用于移除监听器的句柄
@@ -42,7 +94,7 @@ pub struct ListenerHandle {
 impl ListenerHandle {
     pub fn dispose(self) {
         // 调用此方法移除监听器
-        let mut state = self.shared_state.write().unwrap();
+        let mut state = self.shared_state.write_recover();
         state.remove_listener(self.id);
     }
 }
@@ -0,0 +1,94 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+use super::{filter::ContextFilter, state::EventSystemSharedState};
+use crate::error::FrameworkResult;
+use crate::session::Session;
+use crate::sync::RwLockExt;
+
+pub type MiddlewareId = Uuid;
+
+pub type MiddlewareFuture = Pin<Box<dyn Future<Output = FrameworkResult<()>> + Send>>;
+
+// 中间件回调，接收当前会话与用于继续执行后续中间件的 Next
+pub type MiddlewareCallback = Box<dyn Fn(Arc<Session>, Next) -> MiddlewareFuture + Send + Sync>;
+
+// 中间件链的终点（通常为指令执行）
+pub type MiddlewareTerminal = Box<dyn FnOnce(Arc<Session>) -> MiddlewareFuture + Send>;
+
+pub struct RegisteredMiddleware {
+    pub id: MiddlewareId,
+    pub filter: ContextFilter, // 注册时此中间件关联的上下文过滤器
+    pub callback: MiddlewareCallback,
+}
+
+/// 中间件链中“剩余部分”的句柄。
+///
+/// 调用 `run` 会依次执行后续匹配当前会话的中间件，最终执行链的终点；
+/// 不调用则表示在当前中间件处截断。
+pub struct Next {
+    chain: Arc<Vec<Arc<RegisteredMiddleware>>>,
+    index: usize,
+    session: Arc<Session>,
+    terminal: Option<MiddlewareTerminal>,
+}
+
+impl Next {
+    /// 以给定的中间件列表与终点构建一条中间件链。
+    pub fn new(
+        chain: Vec<Arc<RegisteredMiddleware>>,
+        session: Arc<Session>,
+        terminal: Option<MiddlewareTerminal>,
+    ) -> Self {
+        Next {
+            chain: Arc::new(chain),
+            index: 0,
+            session,
+            terminal,
+        }
+    }
+
+    /// 继续执行中间件链的剩余部分。
+    pub async fn run(mut self) -> FrameworkResult<()> {
+        // 跳过过滤器不匹配当前会话的中间件
+        while let Some(middleware) = self.chain.get(self.index) {
+            if middleware.filter.matches_session(&self.session) {
+                break;
+            }
+            self.index += 1;
+        }
+
+        match self.chain.get(self.index) {
+            Some(middleware) => {
+                let middleware = Arc::clone(middleware);
+                let session = Arc::clone(&self.session);
+                let next = Next {
+                    chain: Arc::clone(&self.chain),
+                    index: self.index + 1,
+                    session: Arc::clone(&self.session),
+                    terminal: self.terminal.take(),
+                };
+                (middleware.callback)(session, next).await
+            }
+            None => match self.terminal.take() {
+                Some(terminal) => terminal(self.session).await,
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+// 用于移除中间件的句柄
+pub struct MiddlewareHandle {
+    pub id: MiddlewareId,
+    pub shared_state: Arc<RwLock<EventSystemSharedState>>,
+}
+
+impl MiddlewareHandle {
+    pub fn dispose(self) {
+        let mut state = self.shared_state.write_recover();
+        state.remove_middleware(self.id);
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::sync::MutexExt;
+
+// (平台, 群组 ID, 用户 ID) -> (获取时间, 角色)
+type RoleEntries = HashMap<(String, String, String), (Instant, Vec<String>)>;
+
+/// 未配置时成员角色的缓存时间
+pub const DEFAULT_ROLE_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// 群组成员角色的缓存，存储在共享状态中。
+///
+/// 角色只在需要时（权限检查或按角色过滤）通过 `Bot::get_guild_member` 获取，
+/// 结果按 (平台, 群组 ID, 用户 ID) 缓存 `ttl` 时长，避免每个事件都请求一次平台接口。
+#[derive(Debug, Clone)]
+pub struct MemberRoleCache {
+    /// 缓存的有效时长
+    pub ttl: Duration,
+    entries: Arc<Mutex<RoleEntries>>,
+}
+
+impl Default for MemberRoleCache {
+    fn default() -> Self {
+        MemberRoleCache {
+            ttl: DEFAULT_ROLE_CACHE_TTL,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl MemberRoleCache {
+    /// 获取未过期的缓存角色
+    pub fn get(&self, platform: &str, guild_id: &str, user_id: &str) -> Option<Vec<String>> {
+        let key = (
+            platform.to_string(),
+            guild_id.to_string(),
+            user_id.to_string(),
+        );
+        let mut entries = self.entries.lock_recover();
+        match entries.get(&key) {
+            Some((fetched_at, roles)) if fetched_at.elapsed() < self.ttl => Some(roles.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// 缓存成员的角色
+    pub fn insert(&self, platform: &str, guild_id: &str, user_id: &str, roles: Vec<String>) {
+        let key = (
+            platform.to_string(),
+            guild_id.to_string(),
+            user_id.to_string(),
+        );
+        self.entries
+            .lock_recover()
+            .insert(key, (Instant::now(), roles));
+    }
+
+    /// 清空缓存，例如成员的角色发生变化后
+    pub fn clear(&self) {
+        self.entries.lock_recover().clear();
+    }
+}
@@ -1,26 +1,57 @@
-use crate::command::CommandRegistry;
+use crate::bot::Bot;
+use crate::command::SharedCommandRegistry;
+use crate::command::execution::ExecutionConfig;
+use crate::command::permission::PermissionConfig;
+use crate::command::prefix::PrefixConfig;
+use crate::command::suggest::SuggestionConfig;
+use crate::command::usage::UsageConfig;
+use crate::context::roles::MemberRoleCache;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
 
-use super::listener::{ListenerId, RegisteredListener};
+use super::listener::{ListenerId, ListenerOptions, RegisteredListener};
+use super::middleware::{MiddlewareId, RegisteredMiddleware};
 
 // 共享状态，存储所有事件的监听器
 #[derive(Default)]
 pub struct EventSystemSharedState {
-    /// 事件名 -> 该事件的所有监听器列表
+    /// 事件名 -> 该事件的所有监听器列表（按优先级排序）
     pub listeners_by_event: HashMap<String, Vec<Arc<RegisteredListener>>>,
     /// 监听器ID -> 监听器，用于通过ID快速移除
     pub listeners_by_id: HashMap<ListenerId, Arc<RegisteredListener>>,
+    /// 当前应用的全部机器人实例
+    pub bots: Vec<Arc<Bot>>,
+    /// 按注册顺序排列的中间件
+    pub middlewares: Vec<Arc<RegisteredMiddleware>>,
     /// 存储指令
-    pub command_registry: Arc<RwLock<CommandRegistry>>,
+    pub command_registry: SharedCommandRegistry,
+    /// 指令前缀、机器人昵称与各群组单独设置的前缀
+    pub prefix: PrefixConfig,
+    /// 指令的权限解析器与权限不足时的提示
+    pub permission: PermissionConfig,
+    /// 指令冷却与调用次数的存储
+    pub usage: UsageConfig,
+    /// 未知指令的推测方式与等待确认的指令
+    pub suggestion: SuggestionConfig,
+    /// 指令的执行时限与正在执行的指令
+    pub execution: ExecutionConfig,
+    /// 按需获取的群组成员角色
+    pub member_roles: MemberRoleCache,
 }
 
 impl EventSystemSharedState {
     pub fn add_listener(&mut self, event_name: String, listener: Arc<RegisteredListener>) {
-        self.listeners_by_event
-            .entry(event_name)
-            .or_default()
-            .push(Arc::clone(&listener)); // 将监听器的 Arc 引用存入列表
+        let listeners = self.listeners_by_event.entry(event_name).or_default();
+        // 列表按优先级从高到低排列；同优先级时按注册顺序，prepend 的监听器排在最前
+        let ListenerOptions { priority, prepend } = listener.options;
+        let index = listeners.partition_point(|l| {
+            if prepend {
+                l.options.priority > priority
+            } else {
+                l.options.priority >= priority
+            }
+        });
+        listeners.insert(index, Arc::clone(&listener)); // 将监听器的 Arc 引用存入列表
         self.listeners_by_id.insert(listener.id, listener); // 同时存入 ID 映射
     }
 
@@ -35,4 +66,14 @@ impl EventSystemSharedState {
             false
         }
     }
+
+    pub fn add_middleware(&mut self, middleware: Arc<RegisteredMiddleware>) {
+        self.middlewares.push(middleware);
+    }
+
+    pub fn remove_middleware(&mut self, id_to_remove: MiddlewareId) -> bool {
+        let len_before = self.middlewares.len();
+        self.middlewares.retain(|m| m.id != id_to_remove);
+        self.middlewares.len() != len_before
+    }
 }
@@ -7,7 +7,7 @@ pub enum FrameworkError {
     #[error("WebSocket 连接错误: {0}")]
     WebSocketConnection(String),
     #[error("WebSocket 错误: {0}")]
-    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
     // #[error("HTTP 请求错误: {0}")]
     // Http(#[from] reqwest::Error), // 如果使用 reqwest
     #[error("JSON 序列化/反序列化错误: {0}")]
@@ -28,6 +28,14 @@ pub enum FrameworkError {
     UrlParse(#[from] url::ParseError),
     #[error("内部错误: {0}")]
     Internal(String),
+    #[error("执行时发生 panic: {0}")]
+    Panic(String),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for FrameworkError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        FrameworkError::WebSocket(Box::new(e))
+    }
 }
 
 pub type FrameworkResult<T> = Result<T, FrameworkError>;
@@ -0,0 +1,193 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::command::{Command, ParsedArgs};
+use crate::session::SessionEvent;
+use crate::types::{Argv, Button, Channel, Guild, GuildMember, GuildRole, Login, Message, User};
+
+/// 强类型事件。
+///
+/// 每个事件类型对应一个事件名称，`Bot::dispatch` 会把对应的事件实例
+/// 作为第一个参数传递给该事件的监听器，`Context::on_event` 据此完成向下转型。
+pub trait Event: Send + Sync + 'static {
+    /// 事件名称，与字符串事件名一致
+    const NAME: &'static str;
+
+    /// 从会话事件中提取事件数据，缺少必要数据时返回 `None`
+    fn from_session_event(event: &SessionEvent) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+// 为每个事件生成结构体及其 Event 实现。
+// 分号之前的字段直接从 SessionEvent 中克隆，之后的字段在 SessionEvent 中是可选的，缺失时无法构建事件。
+macro_rules! define_events {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident => $event_name:literal {
+            $($field:ident: $ty:ty),* $(; $($required:ident: $required_ty:ty),*)?
+        }
+    ),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Clone)]
+            pub struct $name {
+                $(pub $field: $ty,)*
+                $($(pub $required: $required_ty,)*)?
+            }
+
+            impl Event for $name {
+                const NAME: &'static str = $event_name;
+
+                fn from_session_event(event: &SessionEvent) -> Option<Self> {
+                    Some($name {
+                        $($field: event.$field.clone(),)*
+                        $($($required: event.$required.clone()?,)*)?
+                    })
+                }
+            }
+        )*
+
+        /// 所有内置强类型事件的名称
+        pub const EVENT_NAMES: &[&str] = &[$($event_name),*];
+
+        /// 将会话事件转换为监听器参数，已知事件会携带对应的强类型事件实例
+        pub fn typed_event_args(event: &SessionEvent) -> Vec<Box<dyn Any + Send + Sync>> {
+            let typed: Option<Box<dyn Any + Send + Sync>> = match event.ty.as_str() {
+                $($event_name => $name::from_session_event(event)
+                    .map(|e| Box::new(e) as Box<dyn Any + Send + Sync>),)*
+                _ => None,
+            };
+            typed.into_iter().collect()
+        }
+    };
+}
+
+define_events! {
+    // 消息事件
+    /// 消息被创建
+    MessageCreated => "message-created" { message: Message, channel: Channel, guild: Guild, user: User },
+    /// 消息被编辑
+    MessageUpdated => "message-updated" { message: Message, channel: Channel, guild: Guild, user: User },
+    /// 消息被删除
+    MessageDeleted => "message-deleted" { message: Message, channel: Channel, guild: Guild, user: User },
+
+    // 群组事件
+    /// 加入群组
+    GuildAdded => "guild-added" { guild: Guild, operator: User },
+    /// 群组被修改
+    GuildUpdated => "guild-updated" { guild: Guild, operator: User },
+    /// 退出群组
+    GuildRemoved => "guild-removed" { guild: Guild, operator: User },
+    /// 接收到新的入群邀请
+    GuildRequest => "guild-request" { guild: Guild, user: User, message: Message },
+
+    // 群组成员事件
+    /// 群组成员增加
+    GuildMemberAdded => "guild-member-added" { guild: Guild, member: GuildMember, user: User, operator: User },
+    /// 群组成员信息更新
+    GuildMemberUpdated => "guild-member-updated" { guild: Guild, member: GuildMember, user: User, operator: User },
+    /// 群组成员移除
+    GuildMemberRemoved => "guild-member-removed" { guild: Guild, member: GuildMember, user: User, operator: User },
+    /// 接收到新的加群请求
+    GuildMemberRequest => "guild-member-request" { guild: Guild, user: User, message: Message },
+
+    // 群组角色事件
+    /// 群组角色被创建
+    GuildRoleCreated => "guild-role-created" { guild: Guild, role: GuildRole },
+    /// 群组角色被修改
+    GuildRoleUpdated => "guild-role-updated" { guild: Guild, role: GuildRole },
+    /// 群组角色被删除
+    GuildRoleDeleted => "guild-role-deleted" { guild: Guild, role: GuildRole },
+
+    // 表情回应事件
+    /// 表态被添加
+    ReactionAdded => "reaction-added" { message: Message, channel: Channel, user: User },
+    /// 表态被移除
+    ReactionRemoved => "reaction-removed" { message: Message, channel: Channel, user: User },
+
+    // 登录事件
+    /// 登录被创建
+    LoginAdded => "login-added" { login: Login },
+    /// 登录被删除
+    LoginRemoved => "login-removed" { login: Login },
+    /// 登录信息更新
+    LoginUpdated => "login-updated" { login: Login },
+
+    // 好友请求事件
+    /// 接收到新的好友申请
+    FriendRequest => "friend-request" { user: User, message: Message },
+
+    // 交互事件
+    /// 类型为 action 的按钮被点击
+    InteractionButton => "interaction/button" { channel: Channel, user: User; button: Button },
+    /// 调用原生指令
+    InteractionCommand => "interaction/command" { channel: Channel, guild: Guild, user: User; argv: Argv },
+}
+
+/// 框架内部错误的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorSource {
+    /// 事件监听器
+    Listener { event: String },
+    /// 中间件
+    Middleware,
+    /// 指令
+    Command,
+    /// 插件
+    Plugin { name: String },
+}
+
+/// 监听器、中间件、指令或插件执行失败（返回错误或 panic）。
+///
+/// 由框架在捕获到错误后发射，不对应任何会话事件。
+#[derive(Debug, Clone)]
+pub struct InternalError {
+    pub source: ErrorSource,
+    pub message: String,
+    /// 错误是否由 panic 引起
+    pub panicked: bool,
+}
+
+impl Event for InternalError {
+    const NAME: &'static str = "internal/error";
+
+    fn from_session_event(_event: &SessionEvent) -> Option<Self> {
+        None
+    }
+}
+
+/// 指令即将执行，此时参数已通过校验。
+///
+/// 由 `CommandRegistry::parse_and_execute` 以串行方式发射，可熔断监听器返回值时指令不会执行，
+/// 返回 `String` 时会将其回复给用户。
+#[derive(Debug, Clone)]
+pub struct CommandBeforeExecute {
+    pub command: Arc<Command>,
+    pub args: ParsedArgs,
+}
+
+impl Event for CommandBeforeExecute {
+    const NAME: &'static str = "command/before-execute";
+
+    fn from_session_event(_event: &SessionEvent) -> Option<Self> {
+        None
+    }
+}
+
+/// 指令执行完毕，无论成功与否都会发射。
+#[derive(Debug, Clone)]
+pub struct CommandAfterExecute {
+    pub command: Arc<Command>,
+    pub args: ParsedArgs,
+    /// 执行失败（返回错误或 panic）时的错误信息
+    pub error: Option<String>,
+}
+
+impl Event for CommandAfterExecute {
+    const NAME: &'static str = "command/after-execute";
+
+    fn from_session_event(_event: &SessionEvent) -> Option<Self> {
+        None
+    }
+}
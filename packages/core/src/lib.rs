@@ -5,7 +5,9 @@ pub mod command; // 添加 command 模块
 pub mod config;
 pub mod context;
 pub mod error;
+pub mod event;
 pub mod message;
 pub mod plugin; // 添加 plugin 模块
 pub mod session; // 添加 session 模块
+pub mod sync;
 pub mod types; // 确保 adapters 模块是公共的
@@ -165,7 +165,7 @@ pub enum MessageElement {
     /// 链接
     #[serde(rename = "a")]
     Link {
-        /// 	链接的 URL
+        /// 链接的 URL
         href: String,
         children: Vec<MessageElement>,
     },
@@ -231,6 +231,49 @@ pub enum MessageElement {
     },
 }
 
+/// 可以转换为一条消息的值，用作指令的返回值，转换结果会被自动发送。
+///
+/// `()`、`None` 以及空的文本或元素列表表示不发送消息。
+pub trait IntoMessage {
+    fn into_message(self) -> Option<Vec<MessageElement>>;
+}
+
+impl IntoMessage for () {
+    fn into_message(self) -> Option<Vec<MessageElement>> {
+        None
+    }
+}
+
+impl IntoMessage for String {
+    fn into_message(self) -> Option<Vec<MessageElement>> {
+        (!self.is_empty()).then(|| vec![MessageElement::Text { text: self }])
+    }
+}
+
+impl IntoMessage for &str {
+    fn into_message(self) -> Option<Vec<MessageElement>> {
+        self.to_string().into_message()
+    }
+}
+
+impl IntoMessage for MessageElement {
+    fn into_message(self) -> Option<Vec<MessageElement>> {
+        Some(vec![self])
+    }
+}
+
+impl IntoMessage for Vec<MessageElement> {
+    fn into_message(self) -> Option<Vec<MessageElement>> {
+        (!self.is_empty()).then_some(self)
+    }
+}
+
+impl<T: IntoMessage> IntoMessage for Option<T> {
+    fn into_message(self) -> Option<Vec<MessageElement>> {
+        self.and_then(IntoMessage::into_message)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
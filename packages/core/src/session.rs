@@ -4,8 +4,11 @@ use crate::bot::Bot;
 use crate::context::Context;
 use crate::error::FrameworkResult;
 use crate::message::MessageElement;
-use crate::types::{Channel, ChannelType, Guild, GuildMember, GuildRole, Login, Message, User};
-use std::sync::Arc;
+use crate::sync::RwLockExt;
+use crate::types::{
+    Argv, Button, Channel, ChannelType, Guild, GuildMember, GuildRole, Login, Message, User,
+};
+use std::sync::{Arc, RwLock};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SessionEvent {
@@ -36,6 +39,12 @@ pub struct SessionEvent {
     pub role: GuildRole,
     /// 事件的目标用户
     pub user: User,
+    /// 交互指令
+    #[serde(default)]
+    pub argv: Option<Argv>,
+    /// 交互按钮
+    #[serde(default)]
+    pub button: Option<Button>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -85,6 +94,8 @@ pub struct Session {
     pub timestamp: i64,
     pub type_: String,
     pub user_id: String,
+    /// 发送者在群组中的角色 ID 列表，`None` 表示尚未获取，通过 `roles`/`resolve_roles` 读取
+    roles: RwLock<Option<Vec<String>>>,
 }
 
 impl Session {
@@ -130,6 +141,7 @@ impl Session {
         let timestamp = event.timestamp;
         let type_ = event.ty.clone();
         let user_id = user.id.clone();
+        let roles = event.member.roles.clone();
 
         Session {
             app,
@@ -153,9 +165,54 @@ impl Session {
             timestamp,
             type_,
             user_id,
+            roles: RwLock::new(roles),
         }
     }
 
+    /// 发送者在群组中的角色 ID 列表，`None` 表示事件未携带且尚未获取
+    pub fn roles(&self) -> Option<Vec<String>> {
+        self.roles.read_recover().clone()
+    }
+
+    /// 获取发送者在群组中的角色。
+    ///
+    /// 事件未携带角色时，先查询共享状态中的角色缓存，未命中再通过 `Bot::get_guild_member` 获取并缓存。
+    /// 私聊等没有群组或用户的会话不会获取；获取失败时返回 `None`，之后的调用会重试。
+    pub async fn resolve_roles(&self) -> Option<Vec<String>> {
+        if let Some(roles) = self.roles() {
+            return Some(roles);
+        }
+        if self.guild_id.is_empty() || self.user_id.is_empty() {
+            return None;
+        }
+        let cache = self.app.shared_state.read_recover().member_roles.clone();
+        let roles = match cache.get(&self.platform, &self.guild_id, &self.user_id) {
+            Some(roles) => roles,
+            None => match self
+                .bot
+                .get_guild_member(&self.guild_id, &self.user_id)
+                .await
+            {
+                Ok(member) => {
+                    let roles = member.roles.unwrap_or_default();
+                    cache.insert(&self.platform, &self.guild_id, &self.user_id, roles.clone());
+                    roles
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "获取用户 {} 在群组 {} 中的角色失败: {}",
+                        self.user_id,
+                        self.guild_id,
+                        e
+                    );
+                    return None;
+                }
+            },
+        };
+        *self.roles.write_recover() = Some(roles.clone());
+        Some(roles)
+    }
+
     /// 在当前上下文发送消息
     pub async fn send(&self, elements: &[MessageElement]) -> FrameworkResult<Vec<String>> {
         self.bot.send_message(&self.channel_id, elements).await
@@ -0,0 +1,73 @@
+use std::any::Any;
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+// 持有锁的代码 panic 后锁会被标记为中毒，之后的每次 unwrap 都会继续 panic。
+// 框架内部的锁只保护注册表一类的数据，panic 不会使其处于不一致的状态，
+// 因此这里直接取回数据并清除中毒标记，避免一个出错的插件拖垮整个机器人。
+
+/// 可从中毒状态恢复的读写锁操作
+pub trait RwLockExt<T: ?Sized> {
+    /// 获取读锁，锁中毒时恢复并继续使用
+    fn read_recover(&self) -> RwLockReadGuard<'_, T>;
+    /// 获取写锁，锁中毒时恢复并继续使用
+    fn write_recover(&self) -> RwLockWriteGuard<'_, T>;
+}
+
+impl<T: ?Sized> RwLockExt<T> for RwLock<T> {
+    fn read_recover(&self) -> RwLockReadGuard<'_, T> {
+        self.read().unwrap_or_else(|poisoned| {
+            tracing::warn!("检测到读写锁中毒，已恢复");
+            self.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+
+    fn write_recover(&self) -> RwLockWriteGuard<'_, T> {
+        self.write().unwrap_or_else(|poisoned| {
+            tracing::warn!("检测到读写锁中毒，已恢复");
+            self.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+}
+
+/// 可从中毒状态恢复的互斥锁操作
+pub trait MutexExt<T: ?Sized> {
+    /// 获取锁，锁中毒时恢复并继续使用
+    fn lock_recover(&self) -> MutexGuard<'_, T>;
+    /// 尝试获取锁，锁被占用时返回 `None`，锁中毒时恢复并继续使用
+    fn try_lock_recover(&self) -> Option<MutexGuard<'_, T>>;
+}
+
+impl<T: ?Sized> MutexExt<T> for Mutex<T> {
+    fn lock_recover(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(|poisoned| {
+            tracing::warn!("检测到互斥锁中毒，已恢复");
+            self.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+
+    fn try_lock_recover(&self) -> Option<MutexGuard<'_, T>> {
+        match self.try_lock() {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::Poisoned(poisoned)) => {
+                tracing::warn!("检测到互斥锁中毒，已恢复");
+                self.clear_poison();
+                Some(poisoned.into_inner())
+            }
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+}
+
+/// 从 panic 的负载中提取可读的错误信息
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "未知的 panic".to_string()
+    }
+}
@@ -91,6 +91,8 @@ pub struct GuildMember {
     /// 加入时间
     #[serde(rename = "joined_at")]
     pub joined_at_ms: Option<i64>,
+    /// 成员拥有的角色 ID 列表
+    pub roles: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -133,7 +135,7 @@ pub struct Login {
     pub features: Vec<String>,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[repr(u8)]
 pub enum LoginStatus {
     /// 离线
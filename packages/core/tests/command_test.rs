@@ -1,8 +1,16 @@
 #[cfg(test)]
 mod tests {
     use shirabe_core::bot::Bot;
+    use shirabe_core::command::args::CommandArgs;
+    use shirabe_core::command::permission::{DefaultPermissionResolver, Permissions};
+    use shirabe_core::command::suggest::SuggestionMode;
+    use shirabe_core::command::usage::{MemoryUsageStore, UsageScope};
     use shirabe_core::command::{
-        Command, CommandAction, CommandBuilder, CommandRegistry, ParsedArgs,
+        Command, CommandBuilder, CommandRegistry, ParsedArgs, cancel,
+        execution::{CancellationToken, DEFAULT_TIMEOUT_MESSAGE},
+        help,
+        schema::ArgValue,
+        shortcut::Regex,
     };
     use shirabe_core::context::Context;
     use shirabe_core::context::filter::ContextFilter;
@@ -11,11 +19,13 @@ mod tests {
     use shirabe_core::message::MessageElement;
     use shirabe_core::session::{Session, SessionEvent};
     use shirabe_core::types::{
-        Channel, ChannelType, Guild, GuildMember, GuildRole, Login, LoginStatus,
+        Argv, Channel, ChannelType, Guild, GuildMember, GuildRole, Login, LoginStatus,
         Message as FrameworkMessage, User,
     };
     use std::collections::HashMap;
     use std::sync::{Arc, Mutex, RwLock};
+    use std::time::Duration;
+    use tokio::sync::Notify;
 
     fn create_test_session(
         platform: &str,
@@ -26,11 +36,41 @@ mod tests {
         message_text_content: &str,
         message_elements: Vec<MessageElement>,
     ) -> Arc<Session> {
+        create_recording_session(
+            platform,
+            user_id,
+            guild_id,
+            channel_id,
+            is_direct,
+            message_text_content,
+            message_elements,
+        )
+        .0
+    }
+
+    fn text(content: &str) -> Vec<MessageElement> {
+        vec![MessageElement::Text {
+            text: content.to_string(),
+        }]
+    }
+
+    // 创建会话，同时返回记录机器人所发送文本消息的列表
+    fn create_recording_session(
+        platform: &str,
+        user_id: &str,
+        guild_id: &str,
+        channel_id: &str,
+        is_direct: bool,
+        message_text_content: &str,
+        message_elements: Vec<MessageElement>,
+    ) -> (Arc<Session>, Arc<Mutex<Vec<String>>>) {
         let shared_state = Arc::new(RwLock::new(EventSystemSharedState::default()));
         let app_context = Arc::new(Context::new_root(shared_state));
 
         #[derive(Debug)]
-        struct DummyAdapter;
+        struct DummyAdapter {
+            sent: Arc<Mutex<Vec<String>>>,
+        }
         #[async_trait::async_trait]
         impl shirabe_core::adapter::Adapter for DummyAdapter {
             fn get_name(&self) -> String {
@@ -124,8 +164,16 @@ mod tests {
             async fn send_message(
                 &self,
                 _channel_id: &str,
-                _elements: &[MessageElement],
+                elements: &[MessageElement],
             ) -> FrameworkResult<Vec<String>> {
+                let text: String = elements
+                    .iter()
+                    .filter_map(|e| match e {
+                        MessageElement::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                self.sent.lock().unwrap().push(text);
                 Ok(vec![])
             }
             async fn send_private_message(
@@ -186,8 +234,9 @@ mod tests {
             ) -> FrameworkResult<()> {
                 unimplemented!()
             }
+            // 权限检查会获取发送者的角色，测试中的成员没有角色
             async fn get_guild_member(&self, _: &str, _: &str) -> FrameworkResult<GuildMember> {
-                unimplemented!()
+                Ok(GuildMember::default())
             }
             async fn get_guild_members(
                 &self,
@@ -225,7 +274,10 @@ mod tests {
                 unimplemented!()
             }
         }
-        let adapter = Arc::new(DummyAdapter);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let adapter = Arc::new(DummyAdapter {
+            sent: Arc::clone(&sent),
+        });
 
         let bot = Arc::new(Bot::new(app_context, adapter));
         let event = SessionEvent {
@@ -298,59 +350,53 @@ mod tests {
                 name: Some("test_user".to_string()),
                 ..Default::default()
             },
+            argv: None,
+            button: None,
         };
-        Arc::new(Session::new(bot, event))
-    }
-
-    fn create_empty_action() -> CommandAction {
-        Box::new(|_session, _args| Box::pin(async { Ok(()) }))
+        (Arc::new(Session::new(bot, event)), sent)
     }
 
     #[test]
     fn test_register_command() {
         let mut registry = CommandRegistry::new();
         let cmd = Command {
-            name: "testcmd".to_string(),
             aliases: vec!["tc".to_string()],
-            description: None,
-            filter: ContextFilter::new(),
-            action: create_empty_action(),
+            ..Command::new("testcmd")
         };
         registry.register(cmd).unwrap();
 
         assert!(registry.commands.contains_key("testcmd"));
         assert!(registry.commands.contains_key("tc"));
-        assert_eq!(registry.commands.get("testcmd").unwrap().name, "testcmd");
-        assert_eq!(registry.commands.get("tc").unwrap().name, "testcmd");
+        assert_eq!(registry.commands.get("testcmd").unwrap()[0].name, "testcmd");
+        assert_eq!(registry.commands.get("tc").unwrap()[0].name, "testcmd");
     }
 
     #[test]
-    fn test_register_overwrite_command() {
+    fn test_register_conflicting_command() {
         let mut registry = CommandRegistry::new();
         let cmd1 = Command {
-            name: "testcmd".to_string(),
-            aliases: vec![],
             description: Some("First version".to_string()),
-            filter: ContextFilter::new(),
-            action: create_empty_action(),
+            ..Command::new("testcmd")
         };
         registry.register(cmd1).unwrap();
         assert_eq!(
-            registry.commands.get("testcmd").unwrap().description,
+            registry.commands.get("testcmd").unwrap()[0].description,
             Some("First version".to_string())
         );
 
         let cmd2 = Command {
-            name: "testcmd".to_string(),
-            aliases: vec![],
             description: Some("Second version".to_string()),
-            filter: ContextFilter::new(),
-            action: create_empty_action(),
+            ..Command::new("testcmd")
         };
-        registry.register(cmd2).unwrap();
+        // 同一上下文中的同名指令冲突，原有的指令保持不变
+        assert!(matches!(
+            registry.register(cmd2),
+            Err(FrameworkError::Command(_))
+        ));
+        assert_eq!(registry.commands.get("testcmd").unwrap().len(), 1);
         assert_eq!(
-            registry.commands.get("testcmd").unwrap().description,
-            Some("Second version".to_string())
+            registry.commands.get("testcmd").unwrap()[0].description,
+            Some("First version".to_string())
         );
     }
 
@@ -368,7 +414,7 @@ mod tests {
         );
         let prefixes = ["/", "!"];
         let executed = registry
-            .parse_and_execute(session, "testcmd arg1", &prefixes)
+            .parse_and_execute(session, &text("testcmd arg1"), &prefixes)
             .await
             .unwrap();
         assert!(!executed);
@@ -388,7 +434,7 @@ mod tests {
         );
         let prefixes = ["/", "!"];
         let executed = registry
-            .parse_and_execute(session, "/", &prefixes)
+            .parse_and_execute(session, &text("/"), &prefixes)
             .await
             .unwrap();
         assert!(!executed);
@@ -408,7 +454,7 @@ mod tests {
         );
         let prefixes = ["/", "!"];
         let executed = registry
-            .parse_and_execute(session, "/unknown", &prefixes)
+            .parse_and_execute(session, &text("/unknown"), &prefixes)
             .await
             .unwrap();
         assert!(!executed);
@@ -421,15 +467,12 @@ mod tests {
         let flag_clone = Arc::clone(&executed_flag);
 
         let cmd = Command {
-            name: "ping".to_string(),
-            aliases: vec![],
-            description: None,
-            filter: ContextFilter::new(),
             action: Box::new(move |_session, _args| {
                 let mut flag = flag_clone.lock().unwrap();
                 *flag = true;
-                Box::pin(async { Ok(()) })
+                Box::pin(async { Ok(None) })
             }),
+            ..Command::new("ping")
         };
         registry.register(cmd).unwrap();
 
@@ -444,7 +487,7 @@ mod tests {
         );
         let prefixes = ["/"];
         let executed = registry
-            .parse_and_execute(session, "/ping", &prefixes)
+            .parse_and_execute(session, &text("/ping"), &prefixes)
             .await
             .unwrap();
 
@@ -459,15 +502,12 @@ mod tests {
         let args_clone = Arc::clone(&received_args);
 
         let cmd = Command {
-            name: "echo".to_string(),
-            aliases: vec![],
-            description: None,
-            filter: ContextFilter::new(),
             action: Box::new(move |_session, args| {
                 let mut received = args_clone.lock().unwrap();
                 *received = args.arguments;
-                Box::pin(async { Ok(()) })
+                Box::pin(async { Ok(None) })
             }),
+            ..Command::new("echo")
         };
         registry.register(cmd).unwrap();
 
@@ -482,14 +522,17 @@ mod tests {
         );
         let prefixes = ["!"];
         let executed = registry
-            .parse_and_execute(session, "!echo hello world", &prefixes)
+            .parse_and_execute(session, &text("!echo hello world"), &prefixes)
             .await
             .unwrap();
 
         assert!(executed);
         assert_eq!(
             *received_args.lock().unwrap(),
-            vec!["hello".to_string(), "world".to_string()]
+            vec![
+                ArgValue::String("hello".to_string()),
+                ArgValue::String("world".to_string())
+            ]
         );
     }
 
@@ -500,15 +543,12 @@ mod tests {
         let options_clone = Arc::clone(&received_options);
 
         let cmd = Command {
-            name: "greet".to_string(),
-            aliases: vec![],
-            description: None,
-            filter: ContextFilter::new(),
             action: Box::new(move |_session, args| {
                 let mut received = options_clone.lock().unwrap();
                 *received = args.options;
-                Box::pin(async { Ok(()) })
+                Box::pin(async { Ok(None) })
             }),
+            ..Command::new("greet")
         };
         registry.register(cmd).unwrap();
 
@@ -523,14 +563,17 @@ mod tests {
         );
         let prefixes = ["/"];
         let executed = registry
-            .parse_and_execute(session, "/greet --name Alice --loud", &prefixes)
+            .parse_and_execute(session, &text("/greet --name Alice --loud"), &prefixes)
             .await
             .unwrap();
 
         assert!(executed);
         let options = received_options.lock().unwrap();
-        assert_eq!(options.get("name"), Some(&"Alice".to_string()));
-        assert_eq!(options.get("loud"), Some(&"".to_string()));
+        assert_eq!(
+            options.get("name"),
+            Some(&ArgValue::String("Alice".to_string()))
+        );
+        assert_eq!(options.get("loud"), Some(&ArgValue::Boolean(true)));
     }
 
     #[tokio::test]
@@ -540,15 +583,12 @@ mod tests {
         let options_clone = Arc::clone(&received_options);
 
         let cmd = Command {
-            name: "shortgreet".to_string(),
-            aliases: vec![],
-            description: None,
-            filter: ContextFilter::new(),
             action: Box::new(move |_session, args| {
                 let mut received = options_clone.lock().unwrap();
                 *received = args.options;
-                Box::pin(async { Ok(()) })
+                Box::pin(async { Ok(None) })
             }),
+            ..Command::new("shortgreet")
         };
         registry.register(cmd).unwrap();
 
@@ -565,7 +605,7 @@ mod tests {
         let executed = registry
             .parse_and_execute(
                 session,
-                "/shortgreet -v -x arg1 --mode test -abc",
+                &text("/shortgreet -v -x arg1 --mode test -abc"),
                 &prefixes,
             )
             .await
@@ -573,12 +613,15 @@ mod tests {
 
         assert!(executed);
         let options = received_options.lock().unwrap();
-        assert_eq!(options.get("v"), Some(&"".to_string()));
-        assert_eq!(options.get("x"), Some(&"".to_string()));
-        assert_eq!(options.get("a"), Some(&"".to_string()));
-        assert_eq!(options.get("b"), Some(&"".to_string()));
-        assert_eq!(options.get("c"), Some(&"".to_string()));
-        assert_eq!(options.get("mode"), Some(&"test".to_string()));
+        assert_eq!(options.get("v"), Some(&ArgValue::Boolean(true)));
+        assert_eq!(options.get("x"), Some(&ArgValue::Boolean(true)));
+        assert_eq!(options.get("a"), Some(&ArgValue::Boolean(true)));
+        assert_eq!(options.get("b"), Some(&ArgValue::Boolean(true)));
+        assert_eq!(options.get("c"), Some(&ArgValue::Boolean(true)));
+        assert_eq!(
+            options.get("mode"),
+            Some(&ArgValue::String("test".to_string()))
+        );
     }
 
     #[tokio::test]
@@ -588,15 +631,12 @@ mod tests {
         let data_clone = Arc::clone(&received_data);
 
         let cmd = Command {
-            name: "mix".to_string(),
-            aliases: vec![],
-            description: None,
-            filter: ContextFilter::new(),
             action: Box::new(move |_session, args| {
                 let mut received = data_clone.lock().unwrap();
                 *received = args;
-                Box::pin(async { Ok(()) })
+                Box::pin(async { Ok(None) })
             }),
+            ..Command::new("mix")
         };
         registry.register(cmd).unwrap();
 
@@ -613,7 +653,7 @@ mod tests {
         let executed = registry
             .parse_and_execute(
                 session,
-                "/mix arg1 --opt1 val1 arg2 --flag -s arg3",
+                &text("/mix arg1 --opt1 val1 arg2 --flag -s arg3"),
                 &prefixes,
             )
             .await
@@ -623,11 +663,21 @@ mod tests {
         let parsed_args = received_data.lock().unwrap();
         assert_eq!(
             parsed_args.arguments,
-            vec!["arg1".to_string(), "arg2".to_string(), "arg3".to_string()]
+            vec![
+                ArgValue::String("arg1".to_string()),
+                ArgValue::String("arg2".to_string()),
+                ArgValue::String("arg3".to_string())
+            ]
+        );
+        assert_eq!(
+            parsed_args.options.get("opt1"),
+            Some(&ArgValue::String("val1".to_string()))
+        );
+        assert_eq!(
+            parsed_args.options.get("flag"),
+            Some(&ArgValue::Boolean(true))
         );
-        assert_eq!(parsed_args.options.get("opt1"), Some(&"val1".to_string()));
-        assert_eq!(parsed_args.options.get("flag"), Some(&"".to_string()));
-        assert_eq!(parsed_args.options.get("s"), Some(&"".to_string()));
+        assert_eq!(parsed_args.options.get("s"), Some(&ArgValue::Boolean(true)));
     }
 
     #[tokio::test]
@@ -653,11 +703,11 @@ mod tests {
         .register()
         .unwrap();
 
-        let registry_guard = registry_arc.read().unwrap();
+        let registry_guard = registry_arc.read().unwrap().clone();
         assert!(registry_guard.commands.contains_key("builtcmd"));
         assert!(registry_guard.commands.contains_key("bc"));
         assert_eq!(
-            registry_guard.commands.get("builtcmd").unwrap().description,
+            registry_guard.commands.get("builtcmd").unwrap()[0].description,
             Some("A built command".to_string())
         );
 
@@ -673,7 +723,7 @@ mod tests {
         );
         let prefixes = ["/"];
         let executed = registry_guard
-            .parse_and_execute(session, "/builtcmd", &prefixes)
+            .parse_and_execute(session, &text("/builtcmd"), &prefixes)
             .await
             .unwrap();
 
@@ -681,6 +731,37 @@ mod tests {
         assert!(*executed_flag.lock().unwrap());
     }
 
+    #[test]
+    fn test_registry_snapshots_are_copy_on_write() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let register = |name: &str| {
+            CommandBuilder::new(
+                name.to_string(),
+                ContextFilter::new(),
+                Arc::clone(&registry_arc),
+            )
+            .action(|_session, _args| async { Ok(()) })
+            .register()
+            .unwrap()
+        };
+
+        // 已取出的快照不受之后注册的影响
+        let snapshot = Arc::clone(&registry_arc.read().unwrap());
+        let handle = register("first");
+        assert!(snapshot.commands.is_empty());
+        assert!(registry_arc.read().unwrap().commands.contains_key("first"));
+
+        // 没有快照在使用时原地修改，不复制注册表
+        drop(snapshot);
+        let before = Arc::as_ptr(&registry_arc.read().unwrap());
+        register("second");
+        handle.dispose();
+        let registry = Arc::clone(&registry_arc.read().unwrap());
+        assert_eq!(Arc::as_ptr(&registry), before);
+        assert!(!registry.commands.contains_key("first"));
+        assert!(registry.commands.contains_key("second"));
+    }
+
     #[test]
     fn test_command_builder_no_action_error() {
         let shared_state = Arc::new(RwLock::new(EventSystemSharedState::default()));
@@ -712,15 +793,13 @@ mod tests {
             .user("user123")
             .platform("test_platform");
         let cmd = Command {
-            name: "filteredcmd".to_string(),
-            aliases: vec![],
-            description: None,
             filter: cmd_filter,
             action: Box::new(move |_session, _args| {
                 let mut flag = flag_clone.lock().unwrap();
                 *flag = true;
-                Box::pin(async { Ok(()) })
+                Box::pin(async { Ok(None) })
             }),
+            ..Command::new("filteredcmd")
         };
         registry.register(cmd).unwrap();
 
@@ -735,7 +814,7 @@ mod tests {
         );
         let prefixes = ["/"];
         let executed = registry
-            .parse_and_execute(Arc::clone(&session_match), "/filteredcmd", &prefixes)
+            .parse_and_execute(Arc::clone(&session_match), &text("/filteredcmd"), &prefixes)
             .await
             .unwrap();
         assert!(executed);
@@ -753,7 +832,7 @@ mod tests {
             vec![],
         );
         let executed = registry
-            .parse_and_execute(session_no_match_user, "/filteredcmd", &prefixes)
+            .parse_and_execute(session_no_match_user, &text("/filteredcmd"), &prefixes)
             .await
             .unwrap();
         assert!(!executed);
@@ -769,10 +848,1749 @@ mod tests {
             vec![],
         );
         let executed = registry
-            .parse_and_execute(session_no_match_platform, "/filteredcmd", &prefixes)
+            .parse_and_execute(session_no_match_platform, &text("/filteredcmd"), &prefixes)
             .await
             .unwrap();
         assert!(!executed);
         assert!(!*executed_flag.lock().unwrap());
     }
+
+    // 通过 CommandBuilder 注册 roll 指令，返回注册表与最近一次收到的参数
+    fn register_roll_command() -> (Arc<CommandRegistry>, Arc<Mutex<Option<ParsedArgs>>>) {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let received = Arc::new(Mutex::new(None));
+        let received_clone = Arc::clone(&received);
+        CommandBuilder::new(
+            "roll <count:integer> [sides:integer=6]".to_string(),
+            ContextFilter::new(),
+            Arc::clone(&registry_arc),
+        )
+        .option("verbose", "-v 显示每次的结果")
+        .option("times", "-t <times:integer=1>")
+        .action(move |_session, args| {
+            *received_clone.lock().unwrap() = Some(args);
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+        let registry = registry_arc.read().unwrap().clone();
+        (registry, received)
+    }
+
+    #[tokio::test]
+    async fn test_declared_arguments_and_options() {
+        let (registry, received) = register_roll_command();
+        let command = &registry.commands.get("roll").unwrap()[0];
+        assert_eq!(command.usage(), "roll <count:integer> [sides:integer=6]");
+        assert_eq!(command.options[0].aliases, vec!["verbose", "v"]);
+        assert_eq!(
+            command.options[0].description.as_deref(),
+            Some("显示每次的结果")
+        );
+
+        let session = create_test_session("p", "u", "g", "c", false, "/roll 3 -v", vec![]);
+        assert!(
+            registry
+                .parse_and_execute(session, &text("/roll 3 -v"), &["/"])
+                .await
+                .unwrap()
+        );
+        let args = received.lock().unwrap().take().unwrap();
+        assert_eq!(args.arguments, vec![ArgValue::Integer(3)]);
+        assert_eq!(args.get("count"), Some(&ArgValue::Integer(3)));
+        assert_eq!(args.get("sides"), Some(&ArgValue::Integer(6)));
+        assert_eq!(args.option("verbose"), Some(&ArgValue::Boolean(true)));
+        assert_eq!(args.option("times"), Some(&ArgValue::Integer(1)));
+
+        let content = "/roll 2 20 --times=4";
+        let session = create_test_session("p", "u", "g", "c", false, content, vec![]);
+        registry
+            .parse_and_execute(session, &text(content), &["/"])
+            .await
+            .unwrap();
+        let args = received.lock().unwrap().take().unwrap();
+        assert_eq!(args.get("sides"), Some(&ArgValue::Integer(20)));
+        assert_eq!(args.option("times"), Some(&ArgValue::Integer(4)));
+        assert_eq!(args.option("verbose"), None);
+    }
+
+    #[tokio::test]
+    async fn test_declared_text_and_mention_arguments() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let received = Arc::new(Mutex::new(None));
+        let received_clone = Arc::clone(&received);
+        CommandBuilder::new(
+            "relay <target:user> <to:channel> [offset:number] [message:text]".to_string(),
+            ContextFilter::new(),
+            Arc::clone(&registry_arc),
+        )
+        .action(move |_session, args| {
+            *received_clone.lock().unwrap() = Some(args);
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+        let registry = registry_arc.read().unwrap().clone();
+
+        let content = "/relay @42 #general -1.5 hello   there";
+        let session = create_test_session("p", "u", "g", "c", false, content, vec![]);
+        registry
+            .parse_and_execute(session, &text(content), &["/"])
+            .await
+            .unwrap();
+        let args = received.lock().unwrap().take().unwrap();
+        assert_eq!(args.get("target"), Some(&ArgValue::User("42".to_string())));
+        assert_eq!(
+            args.get("to"),
+            Some(&ArgValue::Channel("general".to_string()))
+        );
+        assert_eq!(args.get("offset"), Some(&ArgValue::Number(-1.5)));
+        assert_eq!(
+            args.get("message"),
+            Some(&ArgValue::String("hello   there".to_string()))
+        );
+    }
+
+    // 注册只记录参数的指令，返回注册表与最近一次收到的参数
+    fn register_recording_command(
+        declaration: &str,
+    ) -> (Arc<CommandRegistry>, Arc<Mutex<Option<ParsedArgs>>>) {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let received = Arc::new(Mutex::new(None));
+        let received_clone = Arc::clone(&received);
+        CommandBuilder::new(
+            declaration.to_string(),
+            ContextFilter::new(),
+            Arc::clone(&registry_arc),
+        )
+        .option("loud", "-l")
+        .action(move |_session, args| {
+            *received_clone.lock().unwrap() = Some(args);
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+        let registry = registry_arc.read().unwrap().clone();
+        (registry, received)
+    }
+
+    #[tokio::test]
+    async fn test_quoted_and_escaped_arguments() {
+        let (registry, received) = register_recording_command("say <first> [second]");
+        let cases = [
+            (r#"/say "hello world" 'a "b"'"#, "hello world", "a \"b\""),
+            (r#"/say hello\ world \-l"#, "hello world", "-l"),
+            (r#"/say "-l" “中文 引号”"#, "-l", "中文 引号"),
+            (r#"/say "" "\"x\"""#, "", "\"x\""),
+            // 词中的引号按原样保留
+            ("/say don't stop", "don't", "stop"),
+            ("/say it's 5<3", "it's", "5<3"),
+            (r#"/say a"b c"d"#, "a\"b", "c\"d"),
+            // 未闭合的引号视为普通字符
+            ("/say 'tis true", "'tis", "true"),
+            (r#"/say "a b"#, "\"a", "b"),
+        ];
+        for (content, first, second) in cases {
+            let session = create_test_session("p", "u", "g", "c", false, content, vec![]);
+            assert!(
+                registry
+                    .parse_and_execute(session, &text(content), &["/"])
+                    .await
+                    .unwrap()
+            );
+            let args = received.lock().unwrap().take().unwrap();
+            assert_eq!(
+                args.arguments,
+                vec![
+                    ArgValue::String(first.to_string()),
+                    ArgValue::String(second.to_string())
+                ],
+                "Unexpected arguments for {}",
+                content
+            );
+            assert_eq!(args.option("loud"), None, "No option in {}", content);
+        }
+
+        // text 参数获取剩余的整行原文，其中的选项不再解析
+        let (registry, received) = register_recording_command("echo <message:text>");
+        for (content, expected) in [
+            ("/echo -l  say \"hi\"  -l ", "say \"hi\"  -l"),
+            ("/echo \"quoted text\"", "quoted text"),
+            ("/echo don't \"stop me", "don't \"stop me"),
+        ] {
+            let session = create_test_session("p", "u", "g", "c", false, content, vec![]);
+            registry
+                .parse_and_execute(session, &text(content), &["/"])
+                .await
+                .unwrap();
+            let args = received.lock().unwrap().take().unwrap();
+            assert_eq!(
+                args.get("message"),
+                Some(&ArgValue::String(expected.to_string()))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_message_elements_as_arguments() {
+        let (registry, received) =
+            register_recording_command("poke <target:user> <to:channel> [pic:image] [note:text]");
+        let elements = vec![
+            MessageElement::Quote {
+                id: "quoted".to_string(),
+                forward: None,
+                children: vec![],
+            },
+            MessageElement::Text {
+                text: "/poke ".to_string(),
+            },
+            MessageElement::At {
+                id: "42".to_string(),
+                name: Some("Alice".to_string()),
+                role: None,
+                at_type: None,
+            },
+            MessageElement::Sharp {
+                id: "general".to_string(),
+                name: None,
+            },
+            MessageElement::Image {
+                src: "https://example.com/a.png".to_string(),
+                title: None,
+                width: None,
+                height: None,
+                cache: None,
+                timeout: None,
+            },
+            MessageElement::Text {
+                text: " look ".to_string(),
+            },
+            MessageElement::At {
+                id: "7".to_string(),
+                name: None,
+                role: None,
+                at_type: None,
+            },
+        ];
+        let session = create_test_session("p", "u", "g", "c", false, "", elements.clone());
+        assert!(
+            registry
+                .parse_and_execute(session, &elements, &["/"])
+                .await
+                .unwrap()
+        );
+        let args = received.lock().unwrap().take().unwrap();
+        assert_eq!(args.get("target"), Some(&ArgValue::User("42".to_string())));
+        assert_eq!(
+            args.get("to"),
+            Some(&ArgValue::Channel("general".to_string()))
+        );
+        assert_eq!(
+            args.get("pic"),
+            Some(&ArgValue::Image("https://example.com/a.png".to_string()))
+        );
+        assert_eq!(
+            args.get("note"),
+            Some(&ArgValue::String("look <at id=\"7\"/>".to_string()))
+        );
+
+        // 只有文本内容的消息中，元素以标记形式出现，解析结果与元素相同
+        let content = "/poke <at id=\"42\" name=\"Alice\"/><sharp id=\"general\"/> \
+                       <img src=\"https://example.com/a.png?a=1&amp;b=2\"/> look <at id=\"7\"/>";
+        let session = create_test_session("p", "u", "g", "c", false, content, vec![]);
+        assert!(
+            registry
+                .parse_and_execute(session, &text(content), &["/"])
+                .await
+                .unwrap()
+        );
+        let args = received.lock().unwrap().take().unwrap();
+        assert_eq!(args.get("target"), Some(&ArgValue::User("42".to_string())));
+        assert_eq!(
+            args.get("to"),
+            Some(&ArgValue::Channel("general".to_string()))
+        );
+        assert_eq!(
+            args.get("pic"),
+            Some(&ArgValue::Image(
+                "https://example.com/a.png?a=1&b=2".to_string()
+            ))
+        );
+        assert_eq!(
+            args.get("note"),
+            Some(&ArgValue::String("look <at id=\"7\"/>".to_string()))
+        );
+
+        // 引号中的标记与不完整的标记仍是普通文本
+        let content = "/poke '<at id=\"42\"/>' <sharp id=\"general\"";
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, content, vec![]);
+        registry
+            .parse_and_execute(session, &text(content), &["/"])
+            .await
+            .unwrap();
+        assert!(received.lock().unwrap().is_none());
+        assert_eq!(
+            sent.lock().unwrap()[0].lines().next(),
+            Some("参数 to 的值 <sharp 无效，应为频道。")
+        );
+
+        // 类型不匹配的元素会被拒绝
+        let elements = vec![
+            MessageElement::Text {
+                text: "/poke ".to_string(),
+            },
+            MessageElement::Sharp {
+                id: "general".to_string(),
+                name: None,
+            },
+        ];
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        registry
+            .parse_and_execute(session, &elements, &["/"])
+            .await
+            .unwrap();
+        assert!(received.lock().unwrap().is_none());
+        assert_eq!(
+            sent.lock().unwrap()[0].lines().next(),
+            Some("参数 target 的值 <sharp id=\"general\"/> 无效，应为用户。")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_declared_arguments_validation_reply() {
+        let (registry, received) = register_roll_command();
+        let cases = [
+            (
+                "/roll",
+                "缺少参数 count。\n用法：roll <count:integer> [sides:integer=6]",
+            ),
+            (
+                "/roll abc",
+                "参数 count 的值 abc 无效，应为整数。\n用法：roll <count:integer> [sides:integer=6]",
+            ),
+            (
+                "/roll 1 -t",
+                "选项 times 缺少值。\n用法：roll <count:integer> [sides:integer=6]",
+            ),
+        ];
+        for (content, expected) in cases {
+            let (session, sent) =
+                create_recording_session("p", "u", "g", "c", false, content, vec![]);
+            let executed = registry
+                .parse_and_execute(session, &text(content), &["/"])
+                .await
+                .unwrap();
+            assert!(executed);
+            assert_eq!(*sent.lock().unwrap(), vec![expected.to_string()]);
+            assert!(
+                received.lock().unwrap().is_none(),
+                "Action should not run for {}",
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_declaration_is_rejected() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let declarations = [
+            "bad <x:unknown>",
+            "bad [a] <b>",
+            "bad <rest:text> <more>",
+            "bad [n:number=abc]",
+        ];
+        for declaration in declarations {
+            let result = CommandBuilder::new(
+                declaration.to_string(),
+                ContextFilter::new(),
+                Arc::clone(&registry_arc),
+            )
+            .action(|_session, _args| async { Ok(()) })
+            .register();
+            assert!(
+                matches!(result, Err(FrameworkError::Command(_))),
+                "{} should be rejected",
+                declaration
+            );
+        }
+        assert!(registry_arc.read().unwrap().commands.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_help_command() {
+        let content = "/help";
+        let (session, sent) =
+            create_recording_session("test_platform", "u", "g", "c", false, content, vec![]);
+        let ctx = Arc::clone(&session.app);
+        help::register(&ctx).unwrap();
+        ctx.command("roll <count:integer> [sides:integer=6]")
+            .alias("r")
+            .description("掷骰子")
+            .option("verbose", "-v 显示每次的结果")
+            .option("times", "--times <times:integer>")
+            .example("roll 2 20")
+            .action(|_session, _args| async { Ok(()) })
+            .register()
+            .unwrap();
+        ctx.platform("other_platform")
+            .command("hidden")
+            .description("其他平台的指令")
+            .action(|_session, _args| async { Ok(()) })
+            .register()
+            .unwrap();
+        let registry = ctx
+            .shared_state
+            .read()
+            .unwrap()
+            .command_registry
+            .read()
+            .unwrap()
+            .clone();
+
+        registry
+            .parse_and_execute(Arc::clone(&session), &text(content), &["/"])
+            .await
+            .unwrap();
+        registry
+            .parse_and_execute(Arc::clone(&session), &text("/help r"), &["/"])
+            .await
+            .unwrap();
+        registry
+            .parse_and_execute(Arc::clone(&session), &text("/help hidden"), &["/"])
+            .await
+            .unwrap();
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(
+            sent[0],
+            "当前可用的指令有：\n    help  显示指令列表或指定指令的帮助\n    roll  掷骰子\n输入“help 指令名”查看特定指令的用法。"
+        );
+        assert_eq!(
+            sent[1],
+            "roll <count:integer> [sides:integer=6]\n掷骰子\n别名：r\n可用的选项有：\n    -v, --verbose  显示每次的结果\n    --times <integer>\n使用示例：\n    roll 2 20"
+        );
+        assert_eq!(sent[2], "指令 hidden 不存在。");
+    }
+
+    #[tokio::test]
+    async fn test_subcommands() {
+        let (session, sent) =
+            create_recording_session("test_platform", "root", "g", "c", false, "", vec![]);
+        let ctx = Arc::clone(&session.app);
+        help::register(&ctx).unwrap();
+        let executed = Arc::new(Mutex::new(Vec::new()));
+        let register = |ctx: &Context, declaration: &str, alias: Option<&str>| {
+            let executed = Arc::clone(&executed);
+            let mut builder = ctx
+                .command(declaration)
+                .description(&format!("{} 指令", declaration))
+                .action(move |session, args| {
+                    let executed = Arc::clone(&executed);
+                    async move {
+                        let args: Vec<String> =
+                            args.arguments.iter().map(|a| a.to_string()).collect();
+                        executed.lock().unwrap().push(format!(
+                            "{}:{}",
+                            session.user_id,
+                            args.join(",")
+                        ));
+                        Ok(())
+                    }
+                });
+            if let Some(alias) = alias {
+                builder = builder.alias(alias);
+            }
+            builder.register().unwrap();
+        };
+        register(&ctx.user("root"), "admin", Some("adm"));
+        register(&ctx, "admin.ban", None);
+        register(&ctx, "admin.unban", None);
+        // 中间路径 tools 没有注册为指令
+        register(&ctx, "tools.ping", None);
+        let registry = ctx
+            .shared_state
+            .read()
+            .unwrap()
+            .command_registry
+            .read()
+            .unwrap()
+            .clone();
+
+        let resolved = |content: &str| {
+            let tokens: Vec<&str> = content.split_whitespace().collect();
+            registry
+                .resolve(&tokens, &session)
+                .map(|(command, consumed)| (command.name.clone(), consumed))
+        };
+        assert_eq!(
+            resolved("admin ban bob"),
+            Some(("admin.ban".to_string(), 2))
+        );
+        assert_eq!(
+            resolved("admin.ban bob"),
+            Some(("admin.ban".to_string(), 1))
+        );
+        assert_eq!(
+            resolved("adm unban bob"),
+            Some(("admin.unban".to_string(), 2))
+        );
+        assert_eq!(resolved("admin kick bob"), Some(("admin".to_string(), 1)));
+        assert_eq!(resolved("tools ping"), Some(("tools.ping".to_string(), 2)));
+        assert_eq!(resolved("tools"), None);
+
+        for content in ["/admin ban bob", "/adm.unban alice", "/admin other"] {
+            assert!(
+                registry
+                    .parse_and_execute(Arc::clone(&session), &text(content), &["/"])
+                    .await
+                    .unwrap()
+            );
+        }
+        assert_eq!(
+            *executed.lock().unwrap(),
+            vec!["root:bob", "root:alice", "root:other"]
+        );
+
+        // 子指令继承父指令的过滤器
+        let (guest, _) =
+            create_recording_session("test_platform", "guest", "g", "c", false, "", vec![]);
+        assert!(
+            !registry
+                .parse_and_execute(Arc::clone(&guest), &text("/admin ban bob"), &["/"])
+                .await
+                .unwrap()
+        );
+        assert!(
+            registry
+                .parse_and_execute(guest, &text("/tools ping"), &["/"])
+                .await
+                .unwrap()
+        );
+
+        registry
+            .parse_and_execute(Arc::clone(&session), &text("/help"), &["/"])
+            .await
+            .unwrap();
+        registry
+            .parse_and_execute(Arc::clone(&session), &text("/help admin"), &["/"])
+            .await
+            .unwrap();
+        let sent = sent.lock().unwrap();
+        assert_eq!(
+            sent[0],
+            "当前可用的指令有：\n    admin  admin 指令\n        admin.ban  admin.ban 指令\n        admin.unban  admin.unban 指令\n    help  显示指令列表或指定指令的帮助\n    tools.ping  tools.ping 指令\n输入“help 指令名”查看特定指令的用法。"
+        );
+        assert_eq!(
+            sent[1],
+            "admin\nadmin 指令\n别名：adm\n可用的子指令有：\n    admin.ban  admin.ban 指令\n    admin.unban  admin.unban 指令"
+        );
+    }
+
+    // 以指定用户执行指令，返回 action 是否执行以及机器人的第一条回复
+    async fn run_as(
+        registry: &CommandRegistry,
+        executed: &Mutex<u32>,
+        content: &str,
+        user_id: &str,
+        configure: impl FnOnce(&Context),
+    ) -> (bool, Option<String>) {
+        let (session, sent) =
+            create_recording_session("p", user_id, "g", "c", false, content, vec![]);
+        configure(&session.app);
+        let handled = registry
+            .parse_and_execute(Arc::clone(&session), &text(content), &["/"])
+            .await
+            .unwrap();
+        assert!(handled, "{} should be handled", content);
+        let ran = std::mem::take(&mut *executed.lock().unwrap()) == 1;
+        let reply = sent.lock().unwrap().first().cloned();
+        (ran, reply)
+    }
+
+    #[tokio::test]
+    async fn test_command_authority_and_permissions() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let executed = Arc::new(Mutex::new(0));
+        let register = |declaration: &str, configure: fn(CommandBuilder) -> CommandBuilder| {
+            let executed = Arc::clone(&executed);
+            let builder = CommandBuilder::new(
+                declaration.to_string(),
+                ContextFilter::new(),
+                Arc::clone(&registry_arc),
+            );
+            configure(builder)
+                .action(move |_session, _args| {
+                    *executed.lock().unwrap() += 1;
+                    async { Ok(()) }
+                })
+                .register()
+                .unwrap();
+        };
+        register("ban <target:user>", |b| b.authority(3));
+        register("purge", |b| {
+            b.permission("purge")
+                .denial_message("只有管理员可以清理消息。")
+        });
+        register("admin", |b| b.authority(2));
+        register("admin.list", |b| b);
+        register("ping", |b| b);
+        let registry = registry_arc.read().unwrap().clone();
+        let denied = |message: &str| (false, Some(message.to_string()));
+
+        // 默认所有用户的权限等级为 1
+        let guest = |_: &Context| {};
+        assert_eq!(
+            run_as(&registry, &executed, "/ban @42", "guest", guest).await,
+            denied("权限不足。")
+        );
+        assert_eq!(
+            run_as(&registry, &executed, "/admin list", "guest", guest).await,
+            denied("权限不足。"),
+            "Subcommand inherits the parent's authority"
+        );
+        assert_eq!(
+            run_as(&registry, &executed, "/ping", "guest", guest).await,
+            (true, None)
+        );
+
+        // 解析器存储在会话所属应用的共享状态中
+        let configure = |ctx: &Context| {
+            ctx.set_permission_resolver(
+                DefaultPermissionResolver::new()
+                    .user("p", "mod", Permissions::with_authority(3))
+                    .user("p", "root", Permissions::with_authority(3).grant("purge")),
+            );
+            ctx.set_denial_message("你没有权限。");
+        };
+        assert_eq!(
+            run_as(&registry, &executed, "/ban @42", "mod", configure).await,
+            (true, None)
+        );
+        assert_eq!(
+            run_as(&registry, &executed, "/admin list", "mod", configure).await,
+            (true, None)
+        );
+        assert_eq!(
+            run_as(&registry, &executed, "/ban @42", "guest", configure).await,
+            denied("你没有权限。")
+        );
+        assert_eq!(
+            run_as(&registry, &executed, "/purge", "mod", configure).await,
+            denied("只有管理员可以清理消息。")
+        );
+        assert_eq!(
+            run_as(&registry, &executed, "/purge", "root", configure).await,
+            (true, None)
+        );
+
+        // 也可以使用闭包作为解析器
+        let by_closure = |ctx: &Context| {
+            ctx.set_permission_resolver(|session: &Session| {
+                Permissions::with_authority(if session.user_id == "owner" { 5 } else { 0 })
+            })
+        };
+        assert_eq!(
+            run_as(&registry, &executed, "/ban @42", "owner", by_closure).await,
+            (true, None)
+        );
+        assert_eq!(
+            run_as(&registry, &executed, "/ping", "nobody", by_closure).await,
+            (true, None)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_command_cooldown_and_usage_limits() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let executed = Arc::new(Mutex::new(0));
+        let register = |builder: CommandBuilder| {
+            let executed = Arc::clone(&executed);
+            builder
+                .action(move |_session, _args| {
+                    *executed.lock().unwrap() += 1;
+                    async { Ok(()) }
+                })
+                .register()
+                .unwrap();
+        };
+        let builder = |declaration: &str| {
+            CommandBuilder::new(
+                declaration.to_string(),
+                ContextFilter::new(),
+                Arc::clone(&registry_arc),
+            )
+        };
+        register(builder("roll").cooldown(Duration::from_secs(60), UsageScope::User));
+        register(builder("draw").max_usage(2, Duration::from_secs(3600), UsageScope::Global));
+        register(builder("quick").max_usage(1, Duration::from_millis(100), UsageScope::Channel));
+        let registry = registry_arc.read().unwrap().clone();
+
+        // 每次执行使用新的会话与应用，通过共享的存储保留调用记录
+        let store = Arc::new(MemoryUsageStore::new());
+        let with_store = |ctx: &Context| ctx.set_usage_store(Arc::clone(&store));
+        let run = |content: &'static str, user_id: &'static str| {
+            run_as(&registry, &executed, content, user_id, with_store)
+        };
+
+        assert_eq!(run("/roll", "alice").await, (true, None));
+        assert_eq!(
+            run("/roll", "alice").await,
+            (
+                false,
+                Some("指令 roll 冷却中，请在 1 分钟 后重试。".to_string())
+            )
+        );
+        assert_eq!(run("/roll", "bob").await, (true, None));
+
+        assert_eq!(run("/draw", "alice").await, (true, None));
+        assert_eq!(run("/draw", "bob").await, (true, None));
+        assert_eq!(
+            run("/draw", "carol").await,
+            (
+                false,
+                Some("指令 draw 的调用次数已达上限，请在 1 小时 后重试。".to_string())
+            )
+        );
+
+        assert_eq!(run("/quick", "alice").await, (true, None));
+        assert!(run("/quick", "bob").await.1.is_some());
+        tokio::time::advance(Duration::from_millis(150)).await;
+        assert_eq!(run("/quick", "bob").await, (true, None));
+    }
+
+    #[tokio::test]
+    async fn test_command_checks_and_execute_hooks() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let executed = Arc::new(Mutex::new(0));
+        let executed_clone = Arc::clone(&executed);
+        CommandBuilder::new(
+            "deploy <env>".to_string(),
+            ContextFilter::new(),
+            Arc::clone(&registry_arc),
+        )
+        .check(|_session, args| {
+            (args.get("env").and_then(|v| v.as_str()) == Some("prod"))
+                .then(|| "不能部署到生产环境。".to_string())
+        })
+        .check(|session, _args| (session.user_id == "bot").then(|| "机器人不能部署。".to_string()))
+        .action(move |_session, _args| {
+            *executed_clone.lock().unwrap() += 1;
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+        let registry = registry_arc.read().unwrap().clone();
+
+        let audit = Arc::new(Mutex::new(Vec::new()));
+        let hooks = |ctx: &Context| {
+            let before_audit = Arc::clone(&audit);
+            ctx.before_execute(move |session, event| {
+                let audit = Arc::clone(&before_audit);
+                async move {
+                    let env = event.args.get("env").unwrap().to_string();
+                    audit
+                        .lock()
+                        .unwrap()
+                        .push(format!("before {} {}", event.command.name, env));
+                    (session.user_id == "intern").then(|| "实习生不能部署。".to_string())
+                }
+            });
+            let after_audit = Arc::clone(&audit);
+            ctx.after_execute(move |session, event| {
+                let audit = Arc::clone(&after_audit);
+                async move {
+                    audit.lock().unwrap().push(format!(
+                        "after {} by {} error={:?}",
+                        event.command.name, session.user_id, event.error
+                    ));
+                }
+            });
+        };
+
+        assert_eq!(
+            run_as(&registry, &executed, "/deploy staging", "alice", hooks).await,
+            (true, None)
+        );
+        assert_eq!(
+            *audit.lock().unwrap(),
+            vec!["before deploy staging", "after deploy by alice error=None"]
+        );
+        audit.lock().unwrap().clear();
+
+        // 检查按顺序执行，未通过时不会触发钩子
+        assert_eq!(
+            run_as(&registry, &executed, "/deploy prod", "alice", hooks).await,
+            (false, Some("不能部署到生产环境。".to_string()))
+        );
+        assert_eq!(
+            run_as(&registry, &executed, "/deploy staging", "bot", hooks).await,
+            (false, Some("机器人不能部署。".to_string()))
+        );
+        assert!(audit.lock().unwrap().is_empty());
+
+        // 执行前钩子返回提示时中止执行
+        assert_eq!(
+            run_as(&registry, &executed, "/deploy staging", "intern", hooks).await,
+            (false, Some("实习生不能部署。".to_string()))
+        );
+        assert_eq!(*audit.lock().unwrap(), vec!["before deploy staging"]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_before_hook_does_not_consume_usage() {
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        let ctx = Arc::clone(&session.app);
+        let executed = Arc::new(Mutex::new(0));
+        let executed_clone = Arc::clone(&executed);
+        ctx.command("daily")
+            .max_usage(1, Duration::from_secs(86400), UsageScope::User)
+            .cooldown(Duration::from_secs(3600), UsageScope::User)
+            .action(move |_session, _args| {
+                *executed_clone.lock().unwrap() += 1;
+                async { Ok(()) }
+            })
+            .register()
+            .unwrap();
+        let confirmed = Arc::new(Mutex::new(false));
+        let confirmed_clone = Arc::clone(&confirmed);
+        ctx.before_execute(move |_session, _event| {
+            let confirmed = *confirmed_clone.lock().unwrap();
+            async move { (!confirmed).then(|| "请先确认。".to_string()) }
+        });
+        let registry = ctx
+            .shared_state
+            .read()
+            .unwrap()
+            .command_registry
+            .read()
+            .unwrap()
+            .clone();
+
+        let run = || async {
+            registry
+                .parse_and_execute(Arc::clone(&session), &text("/daily"), &["/"])
+                .await
+                .unwrap()
+        };
+        // 被执行前钩子中止的调用不计入冷却与调用次数
+        run().await;
+        run().await;
+        *confirmed.lock().unwrap() = true;
+        run().await;
+        run().await;
+
+        assert_eq!(*executed.lock().unwrap(), 1);
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent[..2], ["请先确认。", "请先确认。"]);
+        assert!(sent[2].starts_with("指令 daily 冷却中"), "{}", sent[2]);
+        assert_eq!(sent.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_after_execute_receives_command_error() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        CommandBuilder::new(
+            "fail".to_string(),
+            ContextFilter::new(),
+            Arc::clone(&registry_arc),
+        )
+        .action(|_session, _args| async {
+            Err::<(), _>(FrameworkError::Command("出错了".to_string()))
+        })
+        .register()
+        .unwrap();
+        let registry = registry_arc.read().unwrap().clone();
+
+        let session = create_test_session("p", "u", "g", "c", false, "/fail", vec![]);
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let errors_clone = Arc::clone(&errors);
+        session.app.after_execute(move |_session, event| {
+            let errors = Arc::clone(&errors_clone);
+            async move { errors.lock().unwrap().push(event.error) }
+        });
+        let result = registry
+            .parse_and_execute(Arc::clone(&session), &text("/fail"), &["/"])
+            .await;
+        assert!(matches!(result, Err(FrameworkError::Command(_))));
+        assert_eq!(
+            *errors.lock().unwrap(),
+            vec![Some(
+                FrameworkError::Command("出错了".to_string()).to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_command_suggestions() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let received = Arc::new(Mutex::new(Vec::new()));
+        for (declaration, filter) in [
+            ("ping", ContextFilter::new()),
+            ("pint", ContextFilter::new()),
+            ("echo <message:text>", ContextFilter::new()),
+            ("admin", ContextFilter::new().user("root")),
+        ] {
+            let received = Arc::clone(&received);
+            CommandBuilder::new(declaration.to_string(), filter, Arc::clone(&registry_arc))
+                .action(move |_session, args| {
+                    received.lock().unwrap().push(args);
+                    async { Ok(()) }
+                })
+                .register()
+                .unwrap();
+        }
+        let registry = registry_arc.read().unwrap().clone();
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        let send = |content: &'static str| {
+            let registry = registry.clone();
+            let session = Arc::clone(&session);
+            async move {
+                registry
+                    .parse_and_execute(session, &text(content), &["/"])
+                    .await
+                    .unwrap()
+            }
+        };
+
+        assert!(send("/pnig").await);
+        assert!(send("/pinh").await);
+        assert!(send("/ehco hi").await);
+        // 不可见的指令不会被推测，相差过大的名称没有推测结果
+        assert!(!send("/admn").await);
+        assert!(!send("/zzz").await);
+        assert_eq!(
+            std::mem::take(&mut *sent.lock().unwrap()),
+            vec![
+                "你要找的是不是“ping”？",
+                "你要找的是不是“ping”或“pint”？",
+                "你要找的是不是“echo”？",
+            ]
+        );
+        assert!(received.lock().unwrap().is_empty());
+
+        // 确认模式下发送句号以相同的参数执行推测的指令
+        session.app.set_suggestion_mode(SuggestionMode::Confirm);
+        assert!(send("/ehco hello  world").await);
+        assert_eq!(
+            std::mem::take(&mut *sent.lock().unwrap()),
+            vec!["你要找的是不是“echo”？发送句号以执行推测的指令。"]
+        );
+        assert!(send("。").await);
+        assert_eq!(
+            received.lock().unwrap().pop().unwrap().get("message"),
+            Some(&ArgValue::String("hello  world".to_string()))
+        );
+        assert!(
+            !send(".").await,
+            "The suggestion is consumed once confirmed"
+        );
+
+        // 其他消息会使推测失效
+        assert!(send("/ehco again").await);
+        assert!(!send("hello").await);
+        assert!(!send(".").await);
+        assert!(received.lock().unwrap().is_empty());
+
+        session.app.set_suggestion_mode(SuggestionMode::Disabled);
+        assert!(!send("/pnig").await);
+    }
+
+    #[tokio::test]
+    async fn test_mention_and_nickname_triggers() {
+        let (registry, received) = register_recording_command("echo [message:text]");
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        session.app.set_nicknames(&["小白", "bot"]);
+        let at = |id: &str| MessageElement::At {
+            id: id.to_string(),
+            name: None,
+            role: None,
+            at_type: None,
+        };
+        let cases = [
+            (
+                vec![
+                    at("test_bot_id"),
+                    MessageElement::Text {
+                        text: " echo hi".to_string(),
+                    },
+                ],
+                true,
+            ),
+            (
+                vec![
+                    at("test_bot_id"),
+                    MessageElement::Text {
+                        text: " /echo hi".to_string(),
+                    },
+                ],
+                true,
+            ),
+            (text("小白，echo hi"), true),
+            (text("小白 echo hi"), true),
+            (text("/echo hi"), true),
+            (text("echo hi"), false),
+            (
+                vec![
+                    at("someone"),
+                    MessageElement::Text {
+                        text: " echo hi".to_string(),
+                    },
+                ],
+                false,
+            ),
+            (vec![at("test_bot_id")], false),
+            (text("小白"), false),
+            (text("bot: echo hi"), true),
+            (text("bot，echo hi"), true),
+            // 昵称必须是完整的词
+            (text("botecho hi"), false),
+            (text("bottle of water"), false),
+            // 只有文本内容的消息中以标记形式出现的提及
+            (text("<at id=\"test_bot_id\"/> echo hi"), true),
+            (text("<at id=\"someone\"/> echo hi"), false),
+        ];
+        for (elements, expected) in cases {
+            let executed = registry
+                .parse_and_execute(Arc::clone(&session), &elements, &["/"])
+                .await
+                .unwrap();
+            assert_eq!(executed, expected, "Unexpected result for {:?}", elements);
+            let args = received.lock().unwrap().take();
+            if expected {
+                assert_eq!(
+                    args.unwrap().get("message"),
+                    Some(&ArgValue::String("hi".to_string()))
+                );
+            }
+        }
+        assert!(sent.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_guild_prefixes() {
+        let guild = create_test_session("p", "u", "g1", "c", false, "", vec![]);
+        let other = create_test_session("p", "u", "g2", "c", false, "", vec![]);
+        // 两个会话属于不同的应用，这里只使用 guild 所属的应用
+        let ctx = &guild.app;
+        ctx.set_prefixes(&["/", "!"]);
+        ctx.set_guild_prefixes("p", "g1", &["#"]);
+        assert_eq!(ctx.guild_prefixes("p", "g1"), Some(vec!["#".to_string()]));
+        assert_eq!(ctx.guild_prefixes("p", "g2"), None);
+
+        let prefixes_for = |session: &Session| {
+            ctx.shared_state
+                .read()
+                .unwrap()
+                .prefix
+                .prefixes_for(session)
+                .to_vec()
+        };
+        assert_eq!(prefixes_for(&guild), vec!["#"]);
+        assert_eq!(prefixes_for(&other), vec!["/", "!"]);
+
+        assert!(ctx.remove_guild_prefixes("p", "g1"));
+        assert!(!ctx.remove_guild_prefixes("p", "g1"));
+        assert_eq!(prefixes_for(&guild), vec!["/", "!"]);
+    }
+
+    #[tokio::test]
+    async fn test_action_return_values_are_sent() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let builder = |declaration: &str| {
+            CommandBuilder::new(
+                declaration.to_string(),
+                ContextFilter::new(),
+                Arc::clone(&registry_arc),
+            )
+        };
+        builder("greet <name>")
+            .action(|_session, args| async move {
+                Ok(format!("你好，{}！", args.get("name").unwrap()))
+            })
+            .register()
+            .unwrap();
+        builder("static")
+            .action(|_session, _args| async { Ok("固定回复") })
+            .register()
+            .unwrap();
+        builder("elements")
+            .action(|_session, _args| async {
+                Ok(vec![
+                    MessageElement::Text {
+                        text: "第一行".to_string(),
+                    },
+                    MessageElement::Text {
+                        text: "第二行".to_string(),
+                    },
+                ])
+            })
+            .register()
+            .unwrap();
+        builder("maybe [flag]")
+            .action(|_session, args| async move {
+                Ok(args.get("flag").map(|_| "有参数".to_string()))
+            })
+            .register()
+            .unwrap();
+        let registry = registry_arc.read().unwrap().clone();
+
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        for content in ["/greet Alice", "/static", "/elements", "/maybe", "/maybe x"] {
+            assert!(
+                registry
+                    .parse_and_execute(Arc::clone(&session), &text(content), &["/"])
+                    .await
+                    .unwrap()
+            );
+        }
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec!["你好，Alice！", "固定回复", "第一行第二行", "有参数"]
+        );
+
+        // 动作可以脱离适配器直接调用，返回值即为将要发送的消息
+        let greet = &registry.commands.get("greet").unwrap()[0];
+        let mut args = ParsedArgs::default();
+        args.named
+            .insert("name".to_string(), ArgValue::String("Bob".to_string()));
+        let reply = (greet.action)(Arc::clone(&session), args).await.unwrap();
+        assert!(matches!(
+            reply.as_deref(),
+            Some([MessageElement::Text { text }]) if text == "你好，Bob！"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_command_shortcuts() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let builder = |declaration: &str, filter: ContextFilter| {
+            CommandBuilder::new(declaration.to_string(), filter, Arc::clone(&registry_arc))
+        };
+        builder("weather <city> [days:integer=1]", ContextFilter::new())
+            .option("detail", "-d")
+            .shortcut(Regex::new(r"^what's the weather in (.+?)\??$").unwrap(), "")
+            .shortcut(Regex::new(r"^(\S+) 未来 (\d+) 天的天气$").unwrap(), "-d")
+            .shortcut("今天天气", "北京")
+            .action(|_session, args| async move {
+                Ok(format!(
+                    "{} {} {}",
+                    args.get("city").unwrap(),
+                    args.get("days").unwrap(),
+                    args.option("detail").is_some()
+                ))
+            })
+            .register()
+            .unwrap();
+        builder("reboot", ContextFilter::new().user("root"))
+            .shortcut("重启吧", "")
+            .action(|_session, _args| async { Ok("重启中") })
+            .register()
+            .unwrap();
+        let registry = registry_arc.read().unwrap().clone();
+
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        session.app.set_nicknames(&["小白"]);
+        let cases = [
+            ("what's the weather in Tokyo", true),
+            ("  what's the weather in New York?", true),
+            ("广州 未来 3 天的天气", true),
+            ("今天天气", true),
+            ("小白，今天天气", true),
+            ("/what's the weather in Paris", true),
+            ("今天天气怎么样", false),
+            // 快捷方式受指令的上下文过滤器限制
+            ("重启吧", false),
+        ];
+        for (content, expected) in cases {
+            let handled = registry
+                .parse_and_execute(Arc::clone(&session), &text(content), &["/"])
+                .await
+                .unwrap();
+            assert_eq!(handled, expected, "Unexpected result for {}", content);
+        }
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![
+                "Tokyo 1 false",
+                "New York 1 false",
+                "广州 3 true",
+                "北京 1 false",
+                "北京 1 false",
+                "Paris 1 false",
+            ]
+        );
+
+        let (root, sent) = create_recording_session("p", "root", "g", "c", false, "", vec![]);
+        assert!(
+            registry
+                .parse_and_execute(root, &text("重启吧"), &["/"])
+                .await
+                .unwrap()
+        );
+        assert_eq!(*sent.lock().unwrap(), vec!["重启中"]);
+    }
+
+    #[tokio::test]
+    async fn test_same_named_commands_in_disjoint_contexts() {
+        let (session_a, sent_a) = create_recording_session("p", "u", "A", "c", false, "", vec![]);
+        let ctx = Arc::clone(&session_a.app);
+        let register = |ctx: &Context, reply: &'static str| {
+            ctx.command("ping")
+                .alias("p")
+                .action(move |_session, _args| async move { Ok(reply) })
+                .register()
+        };
+        let handle_a = register(&ctx.guild("A"), "pong A").unwrap();
+        let handle_b = register(&ctx.guild("B"), "pong B").unwrap();
+        // 与已有指令的上下文重叠时冲突，别名同样参与检查
+        assert!(matches!(
+            register(&ctx, "pong"),
+            Err(FrameworkError::Command(_))
+        ));
+        assert!(matches!(
+            ctx.guild("A")
+                .command("pause")
+                .alias("p")
+                .action(|_session, _args| async { Ok(()) })
+                .register(),
+            Err(FrameworkError::Command(_))
+        ));
+        assert!(
+            ctx.exclude(&ctx.guild("A"))
+                .exclude(&ctx.guild("B"))
+                .command("ping")
+                .action(|_session, _args| async { Ok(()) })
+                .register()
+                .is_err(),
+            "Filters that cannot be analyzed are treated as overlapping"
+        );
+
+        let registry_arc = Arc::clone(&ctx.shared_state.read().unwrap().command_registry);
+        let snapshot = || registry_arc.read().unwrap().clone();
+        assert_eq!(snapshot().commands.get("ping").unwrap().len(), 2);
+        assert!(!snapshot().commands.contains_key("pause"));
+
+        let (session_b, sent_b) = create_recording_session("p", "u", "B", "c", false, "", vec![]);
+        for (session, content) in [(&session_a, "/ping"), (&session_b, "/p")] {
+            assert!(
+                snapshot()
+                    .parse_and_execute(Arc::clone(session), &text(content), &["/"])
+                    .await
+                    .unwrap()
+            );
+        }
+        assert_eq!(*sent_a.lock().unwrap(), vec!["pong A"]);
+        assert_eq!(*sent_b.lock().unwrap(), vec!["pong B"]);
+        // 没有匹配会话的同名指令时视为未注册
+        let (session_c, _) = create_recording_session("p", "u", "C", "c", false, "", vec![]);
+        assert!(snapshot().get("ping", &session_c).is_none());
+        assert!(
+            !snapshot()
+                .parse_and_execute(session_c, &text("/ping"), &["/"])
+                .await
+                .unwrap()
+        );
+
+        // 移除指令及其别名，另一上下文中的同名指令不受影响
+        handle_a.dispose();
+        let registry = snapshot();
+        assert_eq!(registry.commands.get("ping").unwrap().len(), 1);
+        assert_eq!(registry.commands.get("p").unwrap().len(), 1);
+        assert!(
+            !registry
+                .parse_and_execute(Arc::clone(&session_a), &text("/ping"), &["/"])
+                .await
+                .unwrap()
+        );
+        handle_b.dispose();
+        assert!(snapshot().commands.is_empty());
+
+        // 移除后可以在原来的上下文中重新注册
+        register(&ctx, "pong").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_builtin_commands_can_be_overridden() {
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        let ctx = Arc::clone(&session.app);
+        help::register(&ctx).unwrap();
+        cancel::register(&ctx).unwrap();
+        let registry_arc = Arc::clone(&ctx.shared_state.read().unwrap().command_registry);
+
+        // 内置指令不会阻止同名指令的注册，插件的指令在其上下文中取而代之
+        let _help = ctx
+            .guild("g")
+            .command("help")
+            .action(|_session, _args| async { Ok("plugin help") })
+            .register()
+            .unwrap();
+        let registry = registry_arc.read().unwrap().clone();
+        assert_eq!(registry.commands.get("help").unwrap().len(), 2);
+        registry
+            .parse_and_execute(Arc::clone(&session), &text("/help"), &["/"])
+            .await
+            .unwrap();
+        assert_eq!(*sent.lock().unwrap(), vec!["plugin help"]);
+        let listed = registry.visible_commands(&session);
+        assert_eq!(listed.iter().filter(|c| c.name == "help").count(), 1);
+
+        // 其他群组中仍使用内置指令
+        let (other, other_sent) = create_recording_session("p", "u", "h", "c", false, "", vec![]);
+        assert!(registry.get("help", &other).unwrap().overridable);
+        assert!(
+            registry
+                .parse_and_execute(Arc::clone(&other), &text("/help"), &["/"])
+                .await
+                .unwrap()
+        );
+        assert_ne!(*other_sent.lock().unwrap(), vec!["plugin help"]);
+        assert_eq!(other_sent.lock().unwrap().len(), 1);
+
+        // 允许替换的指令不能取代已有的普通指令
+        assert!(matches!(
+            ctx.command("help")
+                .overridable()
+                .action(|_session, _args| async { Ok(()) })
+                .register(),
+            Err(FrameworkError::Command(_))
+        ));
+        // 上下文完全覆盖内置指令时将其移除
+        ctx.command("cancel")
+            .action(|_session, _args| async { Ok(()) })
+            .register()
+            .unwrap();
+        assert_eq!(
+            registry_arc
+                .read()
+                .unwrap()
+                .commands
+                .get("cancel")
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_same_named_commands_have_separate_usage() {
+        let (session_a, sent_a) = create_recording_session("p", "u", "A", "c", false, "", vec![]);
+        let (session_b, sent_b) = create_recording_session("p", "u", "B", "c", false, "", vec![]);
+        let ctx = Arc::clone(&session_a.app);
+        let store = Arc::new(MemoryUsageStore::new());
+        ctx.set_usage_store(Arc::clone(&store));
+        session_b.app.set_usage_store(Arc::clone(&store));
+        for guild in ["A", "B"] {
+            ctx.guild(guild)
+                .command("draw")
+                .max_usage(1, Duration::from_secs(3600), UsageScope::Global)
+                .action(move |_session, _args| async move { Ok(format!("draw {}", guild)) })
+                .register()
+                .unwrap();
+        }
+        let registry_arc = Arc::clone(&ctx.shared_state.read().unwrap().command_registry);
+        let registry = registry_arc.read().unwrap().clone();
+        let draw_a = registry.get("draw", &session_a).unwrap();
+        let draw_b = registry.get("draw", &session_b).unwrap();
+        assert_eq!(draw_a.identity(), "draw@guild=[A]");
+        assert_ne!(draw_a.identity(), draw_b.identity());
+
+        for session in [&session_a, &session_b, &session_a] {
+            registry
+                .parse_and_execute(Arc::clone(session), &text("/draw"), &["/"])
+                .await
+                .unwrap();
+        }
+        // 两个上下文中的指令各自计数，互不消耗对方的次数
+        let sent_a = sent_a.lock().unwrap().clone();
+        assert_eq!(sent_a.len(), 2);
+        assert_eq!(sent_a[0], "draw A");
+        assert!(sent_a[1].contains("调用次数已达上限"));
+        assert_eq!(*sent_b.lock().unwrap(), vec!["draw B"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_argv_and_schemas() {
+        let (registry, received) =
+            register_recording_command("greet <to:user> <times:integer> [note:text]");
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        let argv = |name: &str, argument: &[&str], options: &[(&str, &str)]| Argv {
+            name: name.to_string(),
+            argument: argument.iter().map(|a| a.to_string()).collect(),
+            options: options
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        };
+
+        // 位置参数不会被识别为选项，用户 ID 无需写成提及
+        let executed = registry
+            .execute_argv(
+                Arc::clone(&session),
+                &argv(
+                    "greet",
+                    &["42", "2", "-l hello there"],
+                    &[("loud", "false"), ("tag", "x")],
+                ),
+            )
+            .await
+            .unwrap();
+        assert!(executed);
+        let args = received.lock().unwrap().take().unwrap();
+        assert_eq!(args.get("to"), Some(&ArgValue::User("42".to_string())));
+        assert_eq!(args.get("times"), Some(&ArgValue::Integer(2)));
+        assert_eq!(
+            args.get("note"),
+            Some(&ArgValue::String("-l hello there".to_string()))
+        );
+        assert_eq!(args.option("loud"), None);
+        assert_eq!(args.option("tag"), Some(&ArgValue::String("x".to_string())));
+
+        assert!(
+            registry
+                .execute_argv(Arc::clone(&session), &argv("greet", &["42", "x"], &[]))
+                .await
+                .unwrap()
+        );
+        assert!(received.lock().unwrap().is_none());
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![
+                "参数 times 的值 x 无效，应为整数。\n用法：greet <to:user> <times:integer> [note:text]"
+            ]
+        );
+        assert!(
+            !registry
+                .execute_argv(Arc::clone(&session), &argv("gree", &[], &[]))
+                .await
+                .unwrap()
+        );
+
+        let schemas = registry.schemas(&ContextFilter::new());
+        assert_eq!(
+            serde_json::to_value(&schemas).unwrap(),
+            serde_json::json!([{
+                "name": "greet",
+                "description": null,
+                "arguments": [
+                    { "name": "to", "type": "user", "required": true, "default": null },
+                    { "name": "times", "type": "integer", "required": true, "default": null },
+                    { "name": "note", "type": "text", "required": false, "default": null },
+                ],
+                "options": [{
+                    "name": "loud",
+                    "aliases": ["loud", "l"],
+                    "type": null,
+                    "default": null,
+                    "description": null,
+                }],
+            }])
+        );
+    }
+
+    #[derive(CommandArgs, Debug, PartialEq)]
+    struct RollArgs {
+        count: i64,
+        #[arg(default = 6)]
+        sides: u32,
+        #[arg(ty = "user")]
+        target: Option<String>,
+        /// 显示每次的结果
+        #[option(short = 'v')]
+        verbose: bool,
+        #[option(short = 'o', default = -1, description = "结果的偏移量")]
+        offset: i32,
+        #[option]
+        r#label: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn test_derived_command_args() {
+        let registry_arc = Arc::new(RwLock::new(Arc::new(CommandRegistry::new())));
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = Arc::clone(&received);
+        CommandBuilder::new(
+            "roll".to_string(),
+            ContextFilter::new(),
+            Arc::clone(&registry_arc),
+        )
+        .typed_action(move |_session, args: RollArgs| {
+            let reply = format!("{}d{}", args.count, args.sides);
+            received_clone.lock().unwrap().push(args);
+            async move { Ok(reply) }
+        })
+        .register()
+        .unwrap();
+        assert!(
+            CommandBuilder::new(
+                "bad <x>".to_string(),
+                ContextFilter::new(),
+                Arc::clone(&registry_arc),
+            )
+            .typed_action(|_session, _args: RollArgs| async { Ok(()) })
+            .register()
+            .is_err(),
+            "Arguments cannot be declared twice"
+        );
+        let registry = registry_arc.read().unwrap().clone();
+
+        let command = &registry.commands.get("roll").unwrap()[0];
+        assert_eq!(
+            command.usage(),
+            "roll <count:integer> [sides:integer=6] [target:user]"
+        );
+        let options: Vec<String> = command
+            .options
+            .iter()
+            .map(|option| format!("{} {:?}", option, option.description))
+            .collect();
+        assert_eq!(
+            options,
+            vec![
+                "-v, --verbose Some(\"显示每次的结果\")",
+                "-o, --offset <integer> Some(\"结果的偏移量\")",
+                "--label <string> None",
+            ]
+        );
+
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        for content in ["/roll 2", "/roll 3 20 @alice -v -o 5 --label x", "/roll x"] {
+            assert!(
+                registry
+                    .parse_and_execute(Arc::clone(&session), &text(content), &["/"])
+                    .await
+                    .unwrap()
+            );
+        }
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![
+                RollArgs {
+                    count: 2,
+                    sides: 6,
+                    target: None,
+                    verbose: false,
+                    offset: -1,
+                    label: None,
+                },
+                RollArgs {
+                    count: 3,
+                    sides: 20,
+                    target: Some("alice".to_string()),
+                    verbose: true,
+                    offset: 5,
+                    label: Some("x".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![
+                "2d6",
+                "3d20",
+                "参数 count 的值 x 无效，应为整数。\n用法：roll <count:integer> [sides:integer=6] [target:user]",
+            ]
+        );
+
+        // 缺少非 Option 类型的选项时回复用户；默认值可以包含空白
+        #[derive(CommandArgs)]
+        struct RepeatArgs {
+            #[arg(default = "hello world")]
+            greeting: String,
+            #[option]
+            times: i64,
+        }
+        CommandBuilder::new(
+            "repeat".to_string(),
+            ContextFilter::new(),
+            Arc::clone(&registry_arc),
+        )
+        .typed_action(|_session, args: RepeatArgs| async move {
+            Ok(format!("{} x{}", args.greeting, args.times))
+        })
+        .register()
+        .unwrap();
+        let registry = registry_arc.read().unwrap().clone();
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        for content in ["/repeat", "/repeat --times 2"] {
+            assert!(
+                registry
+                    .parse_and_execute(Arc::clone(&session), &text(content), &["/"])
+                    .await
+                    .unwrap()
+            );
+        }
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![
+                "缺少选项 times。\n用法：repeat [greeting=hello world]",
+                "hello world x2"
+            ]
+        );
+    }
+
+    #[derive(CommandArgs)]
+    struct WaitArgs {
+        #[cancellation]
+        cancellation: CancellationToken,
+        #[arg(default = 60)]
+        seconds: u64,
+    }
+
+    // 注册等待取消的指令，记录其收到的取消令牌，并在动作开始执行时发出通知
+    fn register_waiting_command(
+        ctx: &Context,
+        name: &str,
+        timeout: Option<Duration>,
+    ) -> (Arc<Mutex<Vec<CancellationToken>>>, Arc<Notify>) {
+        let tokens = Arc::new(Mutex::new(Vec::new()));
+        let started = Arc::new(Notify::new());
+        let tokens_clone = Arc::clone(&tokens);
+        let started_clone = Arc::clone(&started);
+        let mut builder = ctx.command(name);
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder
+            .typed_action(move |_session, args: WaitArgs| {
+                tokens_clone.lock().unwrap().push(args.cancellation);
+                started_clone.notify_one();
+                async move {
+                    tokio::time::sleep(Duration::from_secs(args.seconds)).await;
+                    Ok("完成")
+                }
+            })
+            .register()
+            .unwrap();
+        (tokens, started)
+    }
+
+    #[tokio::test]
+    async fn test_command_timeout() {
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        let ctx = Arc::clone(&session.app);
+        let (own, _) = register_waiting_command(&ctx, "own", Some(Duration::from_millis(20)));
+        let (global, _) = register_waiting_command(&ctx, "global", None);
+        ctx.command("quick")
+            .action(|_session, _args| async { Ok("完成") })
+            .register()
+            .unwrap();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let errors_clone = Arc::clone(&errors);
+        ctx.after_execute(move |_session, event| {
+            let errors = Arc::clone(&errors_clone);
+            async move { errors.lock().unwrap().push(event.error) }
+        });
+        let registry = ctx
+            .shared_state
+            .read()
+            .unwrap()
+            .command_registry
+            .read()
+            .unwrap()
+            .clone();
+
+        // 指令单独设置的时限
+        registry
+            .parse_and_execute(Arc::clone(&session), &text("/own"), &["/"])
+            .await
+            .unwrap();
+        assert!(own.lock().unwrap()[0].is_cancelled());
+
+        // 全局时限与自定义的超时提示
+        ctx.set_command_timeout(Some(Duration::from_millis(20)));
+        ctx.set_timeout_message("太慢了");
+        for content in ["/global", "/quick"] {
+            registry
+                .parse_and_execute(Arc::clone(&session), &text(content), &["/"])
+                .await
+                .unwrap();
+        }
+        assert!(global.lock().unwrap()[0].is_cancelled());
+
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![DEFAULT_TIMEOUT_MESSAGE, "太慢了", "完成"]
+        );
+        assert_eq!(
+            *errors.lock().unwrap(),
+            vec![
+                Some("指令执行超时".to_string()),
+                Some("指令执行超时".to_string()),
+                None
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_command() {
+        let (session, sent) = create_recording_session("p", "u", "g", "c", false, "", vec![]);
+        let ctx = Arc::clone(&session.app);
+        cancel::register(&ctx).unwrap();
+        let (tokens, started) = register_waiting_command(&ctx, "wait", None);
+        let registry = ctx
+            .shared_state
+            .read()
+            .unwrap()
+            .command_registry
+            .read()
+            .unwrap()
+            .clone();
+
+        registry
+            .parse_and_execute(Arc::clone(&session), &text("/cancel"), &["/"])
+            .await
+            .unwrap();
+
+        let running = {
+            let registry = registry.clone();
+            let session = Arc::clone(&session);
+            tokio::spawn(async move {
+                registry
+                    .parse_and_execute(session, &text("/wait"), &["/"])
+                    .await
+            })
+        };
+        // 动作开始执行时指令已被记录为正在执行
+        started.notified().await;
+
+        // 其他用户与其他频道无法取消该指令
+        for (user_id, channel_id) in [("other", "c"), ("u", "other")] {
+            let (other, other_sent) =
+                create_recording_session("p", user_id, "g", channel_id, false, "", vec![]);
+            let other = Arc::new(Session::new(
+                Arc::new(Bot::new(Arc::clone(&ctx), Arc::clone(&other.bot.adapter))),
+                other.event.clone(),
+            ));
+            registry
+                .parse_and_execute(other, &text("/cancel"), &["/"])
+                .await
+                .unwrap();
+            assert_eq!(
+                *other_sent.lock().unwrap(),
+                vec!["当前没有正在执行的指令。"]
+            );
+        }
+        assert!(!tokens.lock().unwrap()[0].is_cancelled());
+
+        registry
+            .parse_and_execute(Arc::clone(&session), &text("/cancel"), &["/"])
+            .await
+            .unwrap();
+        assert!(running.await.unwrap().unwrap());
+        assert!(tokens.lock().unwrap()[0].is_cancelled());
+
+        // 已结束的指令不再记录
+        registry
+            .parse_and_execute(Arc::clone(&session), &text("/cancel"), &["/"])
+            .await
+            .unwrap();
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![
+                "当前没有正在执行的指令。",
+                "已取消指令：wait。",
+                "当前没有正在执行的指令。",
+            ]
+        );
+    }
 }
@@ -2,18 +2,31 @@
 mod tests {
     use shirabe_core::adapter::Adapter;
     use shirabe_core::bot::Bot;
-    use shirabe_core::context::{Context, listener::ListenerAction, state::EventSystemSharedState};
+    use shirabe_core::command::permission::{DefaultPermissionResolver, Permissions};
+    use shirabe_core::command::schema::ArgValue;
+    use shirabe_core::context::{
+        Context,
+        listener::{EmitMode, ListenerAction, ListenerOptions},
+        state::EventSystemSharedState,
+    };
     use shirabe_core::error::FrameworkResult;
+    use shirabe_core::event::{
+        ErrorSource, GuildMemberAdded, InteractionCommand, InternalError, LoginAdded, LoginUpdated,
+        MessageCreated,
+    };
     use shirabe_core::message::MessageElement;
     use shirabe_core::session::{Session, SessionEvent};
     use shirabe_core::types::{
-        Channel, ChannelType, Guild, GuildMember, GuildRole, Login, LoginStatus, Message, User,
+        Argv, Channel, ChannelType, Guild, GuildMember, GuildRole, Login, LoginStatus, Message,
+        User,
     };
 
     use async_trait::async_trait;
     use std::any::Any;
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::{Arc, Mutex, RwLock};
+    use std::time::Duration;
     use uuid::Uuid;
 
     // --- Mock Adapter ---
@@ -21,6 +34,8 @@ mod tests {
     struct MockAdapter {
         name: String,
         self_id: String,
+        // get_guild_member 被调用的次数
+        member_lookups: Arc<AtomicUsize>,
     }
 
     #[async_trait]
@@ -240,11 +255,19 @@ mod tests {
             _guild_id: &str,
             user_id: &str,
         ) -> FrameworkResult<GuildMember> {
+            self.member_lookups.fetch_add(1, Ordering::SeqCst);
+            // 以 admin 开头的用户拥有 admin 角色
+            let roles = if user_id.starts_with("admin") {
+                vec!["admin".to_string()]
+            } else {
+                vec![]
+            };
             Ok(GuildMember {
                 user: Some(User {
                     id: user_id.to_string(),
                     ..Default::default()
                 }),
+                roles: Some(roles),
                 ..Default::default()
             })
         }
@@ -348,6 +371,7 @@ mod tests {
                 nick: Some("TestUserNick".to_string()),
                 avatar: None,
                 joined_at_ms: Some(chrono::Utc::now().timestamp_millis()),
+                roles: None,
             },
             message: Message {
                 id: Uuid::new_v4().to_string(),
@@ -393,6 +417,8 @@ mod tests {
                 is_bot: Some(false),
                 nick: None,
             },
+            argv: None,
+            button: None,
         }
     }
 
@@ -405,11 +431,12 @@ mod tests {
         let mock_adapter = Arc::new(MockAdapter {
             name: event.platform.clone(),
             self_id: event.self_id.clone(),
+            member_lookups: Default::default(),
         });
 
         let mut bot_instance =
             Bot::new(Arc::new(bot_platform_ctx), mock_adapter as Arc<dyn Adapter>);
-        bot_instance.self_id = event.self_id.clone();
+        bot_instance.set_self_id(&event.self_id);
         bot_instance.platform = event.platform.clone();
         bot_instance.user = User {
             id: event.self_id.clone(),
@@ -417,21 +444,52 @@ mod tests {
             is_bot: Some(true),
             ..Default::default()
         };
-        bot_instance.state = LoginStatus::Online;
 
         Session::new(Arc::new(bot_instance), event)
     }
 
+    fn create_message_event(user_id: &str, guild_id: &str, content: &str) -> SessionEvent {
+        let mut event = create_minimal_session_event(
+            user_id,
+            Some(guild_id),
+            "channel1",
+            "platform1",
+            ChannelType::Text,
+            "bot_dispatch",
+        );
+        event.ty = "message-created".to_string();
+        event.message.content = content.to_string();
+        event
+    }
+
+    fn create_dispatch_bot(app_ctx: Arc<Context>) -> Arc<Bot> {
+        create_counting_dispatch_bot(app_ctx).0
+    }
+
+    // 创建用于分发事件的机器人，同时返回获取群组成员的次数
+    fn create_counting_dispatch_bot(app_ctx: Arc<Context>) -> (Arc<Bot>, Arc<AtomicUsize>) {
+        let member_lookups = Arc::new(AtomicUsize::new(0));
+        let bot = Arc::new(Bot::new(
+            app_ctx,
+            Arc::new(MockAdapter {
+                name: "platform1".to_string(),
+                self_id: "bot_dispatch".to_string(),
+                member_lookups: Arc::clone(&member_lookups),
+            }),
+        ));
+        (bot, member_lookups)
+    }
+
     #[test]
     fn test_new_root_context() {
         let shared_state = create_shared_state();
         let ctx = Context::new_root(Arc::clone(&shared_state));
-        let bots_guard = ctx.bots.lock().unwrap();
-        assert!(bots_guard.is_empty());
+        assert!(ctx.bots().is_empty());
         assert!(ctx.current_filter.user_ids.is_none());
         assert!(ctx.current_filter.guild_ids.is_none());
         assert!(ctx.current_filter.platforms.is_none());
         assert!(ctx.current_filter.is_private.is_none());
+        assert!(ctx.current_filter.conditions.is_empty());
     }
 
     #[test]
@@ -523,7 +581,7 @@ mod tests {
                 || state
                     .listeners_by_event
                     .get("test_event_once")
-                    .map_or(true, |v| v.is_empty())
+                    .is_none_or(|v| v.is_empty())
         );
     }
 
@@ -549,8 +607,8 @@ mod tests {
 
         let result = ctx.emit("test_event_bail", None, &[]);
 
-        assert_eq!(*bail_called.lock().unwrap(), true);
-        assert_eq!(*subsequent_called.lock().unwrap(), false);
+        assert!(*bail_called.lock().unwrap());
+        assert!(!*subsequent_called.lock().unwrap());
 
         assert!(result.is_some());
         if let Some(value) = result {
@@ -794,7 +852,7 @@ mod tests {
                     || state
                         .listeners_by_event
                         .get("event_handle_dispose")
-                        .map_or(true, |v| v.is_empty())
+                        .is_none_or(|v| v.is_empty())
             );
         }
     }
@@ -895,8 +953,906 @@ mod tests {
                     bail_listener_count, 0,
                     "The bail listener should have been removed"
                 );
-            } else {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_bot_dispatch_emits_event_and_runs_command() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+
+        let event_received = Arc::new(Mutex::new(false));
+        let er_clone = Arc::clone(&event_received);
+        let _h = app_ctx.on("message-created", move |session, _args| {
+            assert!(session.is_some());
+            *er_clone.lock().unwrap() = true;
+        });
+
+        let command_executed = Arc::new(Mutex::new(false));
+        let ce_clone = Arc::clone(&command_executed);
+        app_ctx
+            .command("ping")
+            .action(move |_session, _args| {
+                *ce_clone.lock().unwrap() = true;
+                async { Ok(()) }
+            })
+            .register()
+            .unwrap();
+
+        let event = create_message_event("user1", "guild1", "/ping");
+        let bot = create_dispatch_bot(Arc::clone(&app_ctx));
+        bot.dispatch(event).await.unwrap();
+
+        assert!(
+            *event_received.lock().unwrap(),
+            "message-created listener should have been called"
+        );
+        assert!(
+            *command_executed.lock().unwrap(),
+            "ping command should have been executed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bot_dispatch_routes_interaction_command() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = Arc::clone(&received);
+        app_ctx
+            .command("poke <target:user> [times:integer=1]")
+            .option("loud", "-l")
+            .description("戳一戳")
+            .action(move |_session, args| {
+                received_clone.lock().unwrap().push(args);
+                async { Ok(()) }
+            })
+            .register()
+            .unwrap();
+        app_ctx
+            .platform("other")
+            .command("elsewhere")
+            .action(|_session, _args| async { Ok(()) })
+            .register()
+            .unwrap();
+        // 原生指令不经过中间件
+        let _h = app_ctx.middleware(|_session, _next| async { Ok(()) });
+
+        let bot = create_dispatch_bot(Arc::clone(&app_ctx));
+        let mut event = create_message_event("user1", "guild1", "");
+        event.ty = "interaction/command".to_string();
+        event.argv = Some(Argv {
+            name: "poke".to_string(),
+            argument: vec!["42".to_string()],
+            options: HashMap::from([("l".to_string(), "true".to_string())]),
+        });
+        Arc::clone(&bot).dispatch(event).await.unwrap();
+
+        let args = received.lock().unwrap().pop().expect("poke should run");
+        assert_eq!(args.get("target"), Some(&ArgValue::User("42".to_string())));
+        assert_eq!(args.get("times"), Some(&ArgValue::Integer(1)));
+        assert_eq!(args.option("loud"), Some(&ArgValue::Boolean(true)));
+
+        // 只导出可以在机器人所在平台使用的指令
+        let names: Vec<String> = bot
+            .command_schemas()
+            .into_iter()
+            .map(|schema| schema.name)
+            .collect();
+        assert_eq!(names, vec!["poke"]);
+    }
+
+    fn register_flag_command(ctx: &Context, name: &str) -> Arc<Mutex<bool>> {
+        let flag = Arc::new(Mutex::new(false));
+        let flag_clone = Arc::clone(&flag);
+        ctx.command(name)
+            .action(move |_session, _args| {
+                *flag_clone.lock().unwrap() = true;
+                async { Ok(()) }
+            })
+            .register()
+            .unwrap();
+        flag
+    }
+
+    #[tokio::test]
+    async fn test_middleware_wraps_command_execution() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+        let command_executed = register_flag_command(&app_ctx, "ping");
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let order_outer = Arc::clone(&order);
+        let _h1 = app_ctx.middleware(move |_session, next| {
+            let order = Arc::clone(&order_outer);
+            async move {
+                order.lock().unwrap().push("outer-before");
+                next.run().await?;
+                order.lock().unwrap().push("outer-after");
+                Ok(())
+            }
+        });
+        let order_inner = Arc::clone(&order);
+        let _h2 = app_ctx.middleware(move |_session, next| {
+            let order = Arc::clone(&order_inner);
+            async move {
+                order.lock().unwrap().push("inner");
+                next.run().await
+            }
+        });
+
+        let bot = create_dispatch_bot(Arc::clone(&app_ctx));
+        bot.dispatch(create_message_event("user1", "guild1", "/ping"))
+            .await
+            .unwrap();
+
+        assert!(*command_executed.lock().unwrap());
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["outer-before", "inner", "outer-after"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_middleware_short_circuit_and_filter() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+        let command_executed = register_flag_command(&app_ctx, "ping");
+
+        // 只对 guild_blocked 生效的拦截中间件
+        let handle = app_ctx
+            .guild("guild_blocked")
+            .middleware(|_session, _next| async { Ok(()) });
+
+        let bot = create_dispatch_bot(Arc::clone(&app_ctx));
+        bot.clone()
+            .dispatch(create_message_event("user1", "guild_blocked", "/ping"))
+            .await
+            .unwrap();
+        assert!(
+            !*command_executed.lock().unwrap(),
+            "Middleware should have short-circuited the command"
+        );
+
+        bot.clone()
+            .dispatch(create_message_event("user1", "guild_other", "/ping"))
+            .await
+            .unwrap();
+        assert!(
+            *command_executed.lock().unwrap(),
+            "Middleware filter should not match other guilds"
+        );
+
+        *command_executed.lock().unwrap() = false;
+        handle.dispose();
+        assert!(shared_state.read().unwrap().middlewares.is_empty());
+        bot.dispatch(create_message_event("user1", "guild_blocked", "/ping"))
+            .await
+            .unwrap();
+        assert!(
+            *command_executed.lock().unwrap(),
+            "Disposed middleware should no longer intercept"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_async_listeners_serial() {
+        let shared_state = create_shared_state();
+        let ctx = Context::new_root(Arc::clone(&shared_state));
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let o1 = Arc::clone(&order);
+        let _h1 = ctx.on_async("async_event", move |_session, args| {
+            let order = Arc::clone(&o1);
+            async move {
+                tokio::task::yield_now().await;
+                let value = args[0].downcast_ref::<i32>().copied().unwrap();
+                order.lock().unwrap().push(format!("on:{}", value));
+            }
+        });
+
+        let o2 = Arc::clone(&order);
+        let _h2 = ctx.once_async("async_event", move |_session, _args| async move {
+            o2.lock().unwrap().push("once".to_string());
+        });
+
+        let o3 = Arc::clone(&order);
+        let _h3 = ctx.on("async_event", move |_session, _args| {
+            o3.lock().unwrap().push("sync".to_string());
+        });
+
+        // 同步 emit 不会执行异步监听器
+        ctx.emit("async_event", None, &[Box::new(0i32)]);
+        assert_eq!(*order.lock().unwrap(), vec!["sync".to_string()]);
+        order.lock().unwrap().clear();
+
+        let result = ctx
+            .emit_async("async_event", None, vec![Box::new(1i32)], EmitMode::Serial)
+            .await;
+        assert!(result.is_none());
+        ctx.emit_async("async_event", None, vec![Box::new(2i32)], EmitMode::Serial)
+            .await;
+
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["on:1", "once", "sync", "on:2", "sync"]
+        );
+        let state = shared_state.read().unwrap();
+        assert_eq!(
+            state.listeners_by_event.get("async_event").unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_async_bail_listener() {
+        let shared_state = create_shared_state();
+        let ctx = Context::new_root(Arc::clone(&shared_state));
+        let subsequent_called = Arc::new(Mutex::new(0));
+
+        let h_bail = ctx.bail_async("async_bail", |_session, _args| async {
+            tokio::task::yield_now().await;
+            Some(Box::new("stop".to_string()) as Box<dyn Any + Send + Sync>)
+        });
+        let sc_clone = Arc::clone(&subsequent_called);
+        let _h_after = ctx.on_async("async_bail", move |_session, _args| {
+            let sc = Arc::clone(&sc_clone);
+            async move {
+                *sc.lock().unwrap() += 1;
+            }
+        });
+
+        let result = ctx
+            .emit_async("async_bail", None, vec![], EmitMode::Serial)
+            .await;
+        assert_eq!(
+            result.unwrap().downcast_ref::<String>().unwrap().as_str(),
+            "stop"
+        );
+        assert_eq!(*subsequent_called.lock().unwrap(), 0);
+
+        // 并发模式下不会中途熔断，但仍返回熔断值
+        let result = ctx
+            .emit_async("async_bail", None, vec![], EmitMode::Parallel)
+            .await;
+        assert!(result.is_some());
+        assert_eq!(*subsequent_called.lock().unwrap(), 1);
+
+        h_bail.dispose();
+        let result = ctx
+            .emit_async("async_bail", None, vec![], EmitMode::Serial)
+            .await;
+        assert!(result.is_none());
+        assert_eq!(*subsequent_called.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_async_listeners_parallel() {
+        let shared_state = create_shared_state();
+        let ctx = Context::new_root(Arc::clone(&shared_state));
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let tx = Arc::new(Mutex::new(Some(tx)));
+        let rx = Arc::new(tokio::sync::Mutex::new(Some(rx)));
+
+        // 第一个监听器等待第二个监听器的信号，只有并发执行才能完成
+        let _h1 = ctx.on_async("parallel_event", move |_session, _args| {
+            let rx = Arc::clone(&rx);
+            async move {
+                let receiver = rx.lock().await.take().unwrap();
+                receiver.await.unwrap();
+            }
+        });
+        let _h2 = ctx.on_async("parallel_event", move |_session, _args| {
+            let tx = Arc::clone(&tx);
+            async move {
+                let sender = tx.lock().unwrap().take().unwrap();
+                sender.send(()).unwrap();
+            }
+        });
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            ctx.emit_async("parallel_event", None, vec![], EmitMode::Parallel),
+        )
+        .await;
+        assert!(result.is_ok(), "Parallel listeners should run concurrently");
+    }
+
+    #[tokio::test]
+    async fn test_typed_event_listeners_via_dispatch() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+
+        let joined = Arc::new(Mutex::new(Vec::new()));
+        let joined_clone = Arc::clone(&joined);
+        let _h1 = app_ctx.on_event::<GuildMemberAdded, _>(move |session, ev| {
+            assert!(session.is_some());
+            joined_clone
+                .lock()
+                .unwrap()
+                .push(format!("{}@{}", ev.user.id, ev.guild.id));
+        });
+
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let commands_clone = Arc::clone(&commands);
+        let _h2 = app_ctx.on_event_async::<InteractionCommand, _, _>(move |_session, ev| {
+            let commands = Arc::clone(&commands_clone);
+            async move {
+                commands.lock().unwrap().push(ev.argv.name);
+            }
+        });
+
+        let bot = create_dispatch_bot(Arc::clone(&app_ctx));
+
+        let mut member_event = create_message_event("user1", "guild1", "");
+        member_event.ty = "guild-member-added".to_string();
+        bot.clone().dispatch(member_event).await.unwrap();
+
+        // 缺少 argv 的交互事件无法构建强类型事件，不会触发监听器
+        let mut interaction_event = create_message_event("user1", "guild1", "");
+        interaction_event.ty = "interaction/command".to_string();
+        bot.clone()
+            .dispatch(interaction_event.clone())
+            .await
+            .unwrap();
+        assert!(commands.lock().unwrap().is_empty());
+
+        interaction_event.argv = Some(Argv {
+            name: "weather".to_string(),
+            argument: vec![],
+            options: Default::default(),
+        });
+        bot.dispatch(interaction_event).await.unwrap();
+
+        assert_eq!(*joined.lock().unwrap(), vec!["user1@guild1".to_string()]);
+        assert_eq!(*commands.lock().unwrap(), vec!["weather".to_string()]);
+    }
+
+    #[test]
+    fn test_typed_event_emit_and_type_mismatch() {
+        let shared_state = create_shared_state();
+        let ctx = Context::new_root(Arc::clone(&shared_state));
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let received_clone = Arc::clone(&received);
+        let _h = ctx.on_event::<MessageCreated, _>(move |_session, ev| {
+            received_clone
+                .lock()
+                .unwrap()
+                .push(ev.message.content.clone());
+        });
+
+        let event = create_message_event("user1", "guild1", "typed hello");
+        let typed = MessageCreated {
+            message: event.message.clone(),
+            channel: event.channel.clone(),
+            guild: event.guild.clone(),
+            user: event.user.clone(),
+        };
+        ctx.emit_event(None, typed);
+        // 字符串事件携带了错误类型的参数时，强类型监听器被跳过而不是 panic
+        ctx.emit("message-created", None, &[Box::new(42i32)]);
+
+        assert_eq!(*received.lock().unwrap(), vec!["typed hello".to_string()]);
+    }
+
+    #[test]
+    fn test_listener_priority_ordering() {
+        let shared_state = create_shared_state();
+        let ctx = Context::new_root(Arc::clone(&shared_state));
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let push = |label: &'static str| {
+            let order = Arc::clone(&order);
+            move |_: Option<&Session>, _: &[Box<dyn Any + Send + Sync>]| {
+                order.lock().unwrap().push(label);
+            }
+        };
+
+        let _h1 = ctx.on("priority_event", push("default-1"));
+        let _h2 = ctx.on_with(
+            "priority_event",
+            ListenerOptions::new().priority(-5),
+            push("low"),
+        );
+        let _h3 = ctx.on_with(
+            "priority_event",
+            ListenerOptions::new().priority(10),
+            push("high-1"),
+        );
+        let _h4 = ctx.on("priority_event", push("default-2"));
+        let _h5 = ctx.on_with(
+            "priority_event",
+            ListenerOptions::new().priority(10),
+            push("high-2"),
+        );
+        let _h6 = ctx.on_with(
+            "priority_event",
+            ListenerOptions::new().prepend(),
+            push("default-prepend"),
+        );
+
+        ctx.emit("priority_event", None, &[]);
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec![
+                "high-1",
+                "high-2",
+                "default-prepend",
+                "default-1",
+                "default-2",
+                "low"
+            ]
+        );
+
+        let state = shared_state.read().unwrap();
+        let priorities: Vec<i32> = state
+            .listeners_by_event
+            .get("priority_event")
+            .unwrap()
+            .iter()
+            .map(|l| l.options.priority)
+            .collect();
+        assert_eq!(priorities, vec![10, 10, 0, 0, 0, -5]);
+    }
+
+    #[test]
+    fn test_prepended_bail_runs_before_existing_listeners() {
+        let shared_state = create_shared_state();
+        let ctx = Context::new_root(Arc::clone(&shared_state));
+        let plugin_called = Arc::new(Mutex::new(false));
+
+        let pc_clone = Arc::clone(&plugin_called);
+        let _h_plugin = ctx.on("moderated_event", move |_, _| {
+            *pc_clone.lock().unwrap() = true;
+        });
+        let h_moderation = ctx.bail_with(
+            "moderated_event",
+            ListenerOptions::new().prepend(),
+            |_, _| Some(Box::new("blocked") as Box<dyn Any + Send + Sync>),
+        );
+
+        assert!(ctx.emit("moderated_event", None, &[]).is_some());
+        assert!(
+            !*plugin_called.lock().unwrap(),
+            "Prepended bail listener should run before earlier listeners"
+        );
+
+        h_moderation.dispose();
+        assert!(ctx.emit("moderated_event", None, &[]).is_none());
+        assert!(*plugin_called.lock().unwrap());
+    }
+
+    #[test]
+    fn test_composed_context_filters() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+
+        let session_for = |user: &str, guild: &str, platform: &str| {
+            create_mock_session(
+                Arc::clone(&app_ctx),
+                create_minimal_session_event(
+                    user,
+                    Some(guild),
+                    "channel1",
+                    platform,
+                    ChannelType::Text,
+                    "bot_filter",
+                ),
+            )
+        };
+        let s_a = session_for("userA", "guildX", "platformA");
+        let s_b = session_for("userB", "guildY", "platformB");
+        let s_c = session_for("userC", "guildY", "platformC");
+
+        // platform A 或 user B
+        let union_ctx = app_ctx.platform("platformA").union(&app_ctx.user("userB"));
+        assert!(union_ctx.current_filter.matches_session(&s_a));
+        assert!(union_ctx.current_filter.matches_session(&s_b));
+        assert!(!union_ctx.current_filter.matches_session(&s_c));
+
+        // 除 guildX 以外的所有群组
+        let exclude_ctx = app_ctx.exclude(&app_ctx.guild("guildX"));
+        assert!(!exclude_ctx.current_filter.matches_session(&s_a));
+        assert!(exclude_ctx.current_filter.matches_session(&s_b));
+        assert!(exclude_ctx.current_filter.matches_session(&s_c));
+
+        // guildY 且 platform C
+        let intersect_ctx = app_ctx
+            .guild("guildY")
+            .intersect(&app_ctx.platform("platformC"));
+        assert!(!intersect_ctx.current_filter.matches_session(&s_b));
+        assert!(intersect_ctx.current_filter.matches_session(&s_c));
+
+        // 自定义判定函数可以与其他过滤器继续组合
+        let predicate_ctx = exclude_ctx.filter(|session| session.user_id.ends_with('C'));
+        assert!(!predicate_ctx.current_filter.matches_session(&s_a));
+        assert!(!predicate_ctx.current_filter.matches_session(&s_b));
+        assert!(predicate_ctx.current_filter.matches_session(&s_c));
+
+        // 无会话的通用事件
+        assert!(exclude_ctx.current_filter.matches_generic());
+        assert!(!union_ctx.current_filter.matches_generic());
+        assert!(!predicate_ctx.current_filter.matches_generic());
+    }
+
+    #[tokio::test]
+    async fn test_composed_filter_applies_to_listeners_and_commands() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+        let scoped_ctx = app_ctx.exclude(&app_ctx.guild("guild_blocked"));
+
+        let listener_count = Arc::new(Mutex::new(0));
+        let lc_clone = Arc::clone(&listener_count);
+        let _h = scoped_ctx.on("message-created", move |_, _| {
+            *lc_clone.lock().unwrap() += 1;
+        });
+        let command_executed = register_flag_command(&scoped_ctx, "ping");
+
+        let bot = create_dispatch_bot(Arc::clone(&app_ctx));
+        bot.clone()
+            .dispatch(create_message_event("user1", "guild_blocked", "/ping"))
+            .await
+            .unwrap();
+        assert_eq!(*listener_count.lock().unwrap(), 0);
+        assert!(!*command_executed.lock().unwrap());
+
+        bot.dispatch(create_message_event("user1", "guild_open", "/ping"))
+            .await
+            .unwrap();
+        assert_eq!(*listener_count.lock().unwrap(), 1);
+        assert!(*command_executed.lock().unwrap());
+    }
+
+    #[test]
+    fn test_channel_and_self_id_contexts() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+
+        let channel_ctx = app_ctx.channel("channel1");
+        assert_eq!(
+            channel_ctx.current_filter.channel_ids,
+            Some(HashSet::from(["channel1".to_string()]))
+        );
+        let bot_a_ctx = app_ctx.self_id("bot_a");
+
+        let event_for = |channel: &str, self_id: &str| {
+            create_minimal_session_event(
+                "user1",
+                Some("guild1"),
+                channel,
+                "platform1",
+                ChannelType::Text,
+                self_id,
+            )
+        };
+        let s1 = create_mock_session(Arc::clone(&app_ctx), event_for("channel1", "bot_a"));
+        let s2 = create_mock_session(Arc::clone(&app_ctx), event_for("channel2", "bot_b"));
+
+        assert!(channel_ctx.current_filter.matches_session(&s1));
+        assert!(!channel_ctx.current_filter.matches_session(&s2));
+        assert!(bot_a_ctx.current_filter.matches_session(&s1));
+        assert!(!bot_a_ctx.current_filter.matches_session(&s2));
+        assert!(!bot_a_ctx.current_filter.matches_generic());
+    }
+
+    #[tokio::test]
+    async fn test_role_context_resolves_member_roles() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+        let admin_ctx = app_ctx.role("admin");
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let calls_clone = Arc::clone(&calls);
+        let _h = admin_ctx.on("message-created", move |session, _| {
+            calls_clone
+                .lock()
+                .unwrap()
+                .push(session.unwrap().user_id.clone());
+        });
+
+        let (bot, member_lookups) = create_counting_dispatch_bot(Arc::clone(&app_ctx));
+        // 没有按角色过滤的监听器的事件不获取角色
+        let mut event = create_message_event("admin1", "guild1", "");
+        event.ty = "reaction-added".to_string();
+        Arc::clone(&bot).dispatch(event).await.unwrap();
+        assert_eq!(member_lookups.load(Ordering::SeqCst), 0);
+
+        bot.clone()
+            .dispatch(create_message_event("member1", "guild1", "hi"))
+            .await
+            .unwrap();
+        bot.dispatch(create_message_event("admin1", "guild1", "hi"))
+            .await
+            .unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["admin1".to_string()]);
+        assert_eq!(member_lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_shared_bot_registry() {
+        let app = shirabe_core::app::App::new();
+        // 在 run() 之前获取的上下文同样能看到之后注册的机器人
+        let plugin_ctx = app.context();
+        let runtime_ctx = Arc::new(app.context());
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let added = Arc::clone(&events);
+        let _h1 = plugin_ctx.on("bot-added", move |_, args| {
+            let bot = args[0].downcast_ref::<Arc<Bot>>().unwrap();
+            added
+                .lock()
+                .unwrap()
+                .push(format!("added:{}", bot.self_id()));
+        });
+        // 异步监听器同样会被执行
+        let removed = Arc::clone(&events);
+        let _h2 = plugin_ctx.on_async("bot-removed", move |_, args| {
+            let removed = Arc::clone(&removed);
+            async move {
+                tokio::task::yield_now().await;
+                let bot = args[0].downcast_ref::<Arc<Bot>>().unwrap();
+                removed
+                    .lock()
+                    .unwrap()
+                    .push(format!("removed:{}", bot.self_id()));
+            }
+        });
+
+        let new_bot = |platform: &str, self_id: &str| {
+            let bot = Bot::new(
+                Arc::clone(&runtime_ctx),
+                Arc::new(MockAdapter {
+                    name: platform.to_string(),
+                    self_id: self_id.to_string(),
+                    member_lookups: Default::default(),
+                }),
+            );
+            bot.set_self_id(self_id);
+            Arc::new(bot)
+        };
+        let bot_a = new_bot("platform1", "bot_a");
+        let bot_b = new_bot("platform1", "bot_b");
+        let bot_c = new_bot("platform2", "bot_c");
+        runtime_ctx.add_bot(Arc::clone(&bot_a)).await;
+        runtime_ctx.add_bot(Arc::clone(&bot_b)).await;
+        runtime_ctx.add_bot(Arc::clone(&bot_c)).await;
+
+        assert_eq!(plugin_ctx.bots().len(), 3);
+        let found = plugin_ctx
+            .guild("guild1")
+            .bot("platform1", "bot_b")
+            .unwrap();
+        assert!(Arc::ptr_eq(&found, &bot_b));
+        assert!(plugin_ctx.bot("platform2", "bot_b").is_none());
+        assert_eq!(plugin_ctx.bots_by_platform("platform1").len(), 2);
+
+        assert!(plugin_ctx.remove_bot(&bot_a).await);
+        assert!(!plugin_ctx.remove_bot(&bot_a).await);
+        assert_eq!(runtime_ctx.bots_by_platform("platform1").len(), 1);
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["added:bot_a", "added:bot_b", "added:bot_c", "removed:bot_a"]
+        );
+
+        // 适配器没有设置机器人账号时，分发事件后即可按账号查找
+        let dispatch_bot = create_dispatch_bot(Arc::clone(&runtime_ctx));
+        assert_eq!(dispatch_bot.self_id(), "");
+        runtime_ctx.add_bot(Arc::clone(&dispatch_bot)).await;
+        assert!(plugin_ctx.bot("platform1", "bot_dispatch").is_none());
+        Arc::clone(&dispatch_bot)
+            .dispatch(create_message_event("user1", "guild1", "hello"))
+            .await
+            .unwrap();
+        let found = plugin_ctx.bot("platform1", "bot_dispatch").unwrap();
+        assert!(Arc::ptr_eq(&found, &dispatch_bot));
+    }
+
+    // 收集 internal/error 事件
+    fn collect_internal_errors(ctx: &Context) -> Arc<Mutex<Vec<InternalError>>> {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let errors_clone = Arc::clone(&errors);
+        ctx.on_event::<InternalError, _>(move |_, error| {
+            errors_clone.lock().unwrap().push(error.clone());
+        });
+        errors
+    }
+
+    #[tokio::test]
+    async fn test_listener_panic_is_isolated() {
+        let shared_state = create_shared_state();
+        let ctx = Context::new_root(Arc::clone(&shared_state));
+        let errors = collect_internal_errors(&ctx);
+
+        let counter = Arc::new(Mutex::new(0));
+        let _h1 = ctx.on("test_event", |_, _| panic!("sync boom"));
+        let counter_sync = Arc::clone(&counter);
+        let _h2 = ctx.on("test_event", move |_, _| {
+            *counter_sync.lock().unwrap() += 1;
+        });
+        let _h3 = ctx.on_async("test_event", |_, _| async { panic!("async boom") });
+        let counter_async = Arc::clone(&counter);
+        let _h4 = ctx.on_async("test_event", move |_, _| {
+            let counter = Arc::clone(&counter_async);
+            async move {
+                *counter.lock().unwrap() += 1;
+            }
+        });
+
+        // 同一个监听器 panic 之后仍然可以再次执行
+        ctx.emit("test_event", None, &[]);
+        ctx.emit("test_event", None, &[]);
+        assert_eq!(*counter.lock().unwrap(), 2);
+
+        ctx.emit_async("test_event", None, vec![], EmitMode::Serial)
+            .await;
+        ctx.emit_async("test_event", None, vec![], EmitMode::Parallel)
+            .await;
+        assert_eq!(*counter.lock().unwrap(), 6);
+
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 6);
+        assert!(errors.iter().all(|e| e.panicked
+            && e.source
+                == ErrorSource::Listener {
+                    event: "test_event".to_string()
+                }));
+        assert!(errors[0].message.contains("sync boom"));
+        assert!(errors[3].message.contains("async boom"));
+    }
+
+    fn explode() -> FrameworkResult<()> {
+        panic!("command boom")
+    }
+
+    #[tokio::test]
+    async fn test_command_panic_is_reported() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+        let errors = collect_internal_errors(&app_ctx);
+        app_ctx
+            .command("boom")
+            .action(|_session, _args| async { explode() })
+            .register()
+            .unwrap();
+        let command_executed = register_flag_command(&app_ctx, "ping");
+
+        let bot = create_dispatch_bot(Arc::clone(&app_ctx));
+        bot.clone()
+            .dispatch(create_message_event("user1", "guild1", "/boom"))
+            .await
+            .unwrap();
+        bot.dispatch(create_message_event("user1", "guild1", "/ping"))
+            .await
+            .unwrap();
+
+        assert!(*command_executed.lock().unwrap());
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].source, ErrorSource::Command);
+        assert!(errors[0].panicked);
+        assert!(errors[0].message.contains("command boom"));
+    }
+
+    #[test]
+    fn test_recovers_from_lock_poisoning() {
+        let shared_state = create_shared_state();
+        let ctx = Context::new_root(Arc::clone(&shared_state));
+
+        let poison_state = Arc::clone(&shared_state);
+        let result = std::thread::spawn(move || {
+            let _guard = poison_state.write().unwrap();
+            panic!("poison shared state");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(shared_state.is_poisoned());
+
+        let fired = Arc::new(Mutex::new(false));
+        let fired_clone = Arc::clone(&fired);
+        let _handle = ctx.on("test_event", move |_, _| {
+            *fired_clone.lock().unwrap() = true;
+        });
+        ctx.emit("test_event", None, &[]);
+        assert!(*fired.lock().unwrap());
+        assert!(!shared_state.is_poisoned());
+    }
+
+    #[tokio::test]
+    async fn test_login_status_events() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let added = Arc::clone(&events);
+        let _h1 = app_ctx.on_event::<LoginAdded, _>(move |_, event| {
+            added
+                .lock()
+                .unwrap()
+                .push(format!("added:{:?}", event.login.status));
+        });
+        // 异步监听器同样会被执行
+        let updated = Arc::clone(&events);
+        let _h2 = app_ctx.on_event_async::<LoginUpdated, _, _>(move |_, event| {
+            let updated = Arc::clone(&updated);
+            async move {
+                tokio::task::yield_now().await;
+                updated
+                    .lock()
+                    .unwrap()
+                    .push(format!("updated:{:?}", event.login.status));
+            }
+        });
+
+        let bot = create_dispatch_bot(Arc::clone(&app_ctx));
+        assert_eq!(bot.status(), LoginStatus::Offline);
+        bot.set_status(LoginStatus::Connect).await;
+        bot.online().await;
+        bot.online().await; // 状态未变化，不发射事件
+        bot.offline().await;
+
+        assert_eq!(bot.status(), LoginStatus::Offline);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["added:Connect", "updated:Online", "updated:Offline"]
+        );
+    }
+    #[tokio::test]
+    async fn test_member_roles_resolved_only_for_permission_checks() {
+        let shared_state = create_shared_state();
+        let app_ctx = Arc::new(Context::new_root(Arc::clone(&shared_state)));
+        app_ctx.set_permission_resolver(
+            DefaultPermissionResolver::new().role("admin", Permissions::with_authority(3)),
+        );
+        let executed = Arc::new(Mutex::new(Vec::new()));
+        for (name, authority) in [("ping", 0), ("ban", 3)] {
+            let executed = Arc::clone(&executed);
+            app_ctx
+                .command(name)
+                .authority(authority)
+                .action(move |session, _args| {
+                    executed
+                        .lock()
+                        .unwrap()
+                        .push(format!("{}:{}", session.user_id, name));
+                    async { Ok(()) }
+                })
+                .register()
+                .unwrap();
+        }
+        let (bot, member_lookups) = create_counting_dispatch_bot(Arc::clone(&app_ctx));
+
+        // 普通消息与不要求权限的指令不获取角色
+        for content in ["hi", "/ping"] {
+            Arc::clone(&bot)
+                .dispatch(create_message_event("admin1", "guild1", content))
+                .await
+                .unwrap();
+        }
+        assert_eq!(member_lookups.load(Ordering::SeqCst), 0);
+
+        // 同一成员的角色会被缓存
+        for user_id in ["admin1", "admin1", "member1"] {
+            Arc::clone(&bot)
+                .dispatch(create_message_event(user_id, "guild1", "/ban"))
+                .await
+                .unwrap();
+        }
+        assert_eq!(member_lookups.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            *executed.lock().unwrap(),
+            vec!["admin1:ping", "admin1:ban", "admin1:ban"]
+        );
+
+        // 缓存过期后重新获取
+        app_ctx.set_role_cache_ttl(Duration::ZERO);
+        bot.dispatch(create_message_event("admin1", "guild1", "/ban"))
+            .await
+            .unwrap();
+        assert_eq!(member_lookups.load(Ordering::SeqCst), 3);
+    }
 }
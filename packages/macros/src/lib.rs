@@ -0,0 +1,190 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Expr, Fields, Lit, UnOp, parse_macro_input};
+
+/// 为结构体实现 `shirabe_core::command::args::CommandArgs`。
+///
+/// 字段默认为位置参数，带 `#[option(...)]` 的字段为选项，带 `#[cancellation]` 的字段接收指令的取消令牌，
+/// 可用的属性见 `CommandArgs` 的文档。
+#[proc_macro_derive(CommandArgs, attributes(arg, option, cancellation))]
+pub fn derive_command_args(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// 字段上的属性
+#[derive(Default)]
+struct FieldAttrs {
+    is_option: bool,
+    is_cancellation: bool,
+    short: Option<char>,
+    ty: Option<String>,
+    default: Option<String>,
+    description: Option<String>,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "CommandArgs 不支持泛型结构体",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.ident.span(),
+                    "CommandArgs 只能用于具名字段的结构体",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "CommandArgs 只能用于结构体",
+            ));
+        }
+    };
+
+    let args = quote!(::shirabe_core::command::args);
+    let mut arguments = Vec::new();
+    let mut options = Vec::new();
+    let mut values = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("具名字段");
+        let name = ident.unraw().to_string();
+        let ty = &field.ty;
+        let attrs = parse_field_attrs(field)?;
+        let ty_name = optional_str(attrs.ty.as_deref());
+        let default = optional_str(attrs.default.as_deref());
+        if attrs.is_cancellation {
+            values.push(quote! {
+                #ident: ::std::clone::Clone::clone(&args.cancellation)
+            });
+        } else if attrs.is_option {
+            let short = match attrs.short {
+                Some(short) => quote!(::std::option::Option::Some(#short)),
+                None => quote!(::std::option::Option::None),
+            };
+            let description = optional_str(attrs.description.as_deref());
+            options.push(quote! {
+                #args::option_def::<#ty>(#name, #short, #ty_name, #default, #description)?
+            });
+            values.push(quote! {
+                #ident: #args::option_value::<#ty>(#name, args.option(#name))?
+            });
+        } else {
+            arguments.push(quote! {
+                #args::argument_def::<#ty>(#name, #ty_name, #default)?
+            });
+            values.push(quote! {
+                #ident: #args::field_value::<#ty>(#name, args.get(#name))?
+            });
+        }
+    }
+
+    let ident = &input.ident;
+    Ok(quote! {
+        impl #args::CommandArgs for #ident {
+            fn arguments() -> ::std::result::Result<
+                ::std::vec::Vec<::shirabe_core::command::schema::ArgumentDef>,
+                ::std::string::String,
+            > {
+                ::std::result::Result::Ok(::std::vec![#(#arguments),*])
+            }
+
+            fn options() -> ::std::result::Result<
+                ::std::vec::Vec<::shirabe_core::command::schema::OptionDef>,
+                ::std::string::String,
+            > {
+                ::std::result::Result::Ok(::std::vec![#(#options),*])
+            }
+
+            fn from_args(
+                args: &::shirabe_core::command::ParsedArgs,
+            ) -> ::std::result::Result<Self, ::std::string::String> {
+                ::std::result::Result::Ok(Self { #(#values),* })
+            }
+        }
+    })
+}
+
+fn optional_str(value: Option<&str>) -> TokenStream2 {
+    match value {
+        Some(value) => quote!(::std::option::Option::Some(#value)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+fn parse_field_attrs(field: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    let mut docs = Vec::new();
+    for attr in &field.attrs {
+        if attr.path().is_ident("doc") {
+            if let syn::Meta::NameValue(meta) = &attr.meta
+                && let Expr::Lit(expr) = &meta.value
+                && let Lit::Str(doc) = &expr.lit
+            {
+                docs.push(doc.value().trim().to_string());
+            }
+            continue;
+        }
+        if attr.path().is_ident("cancellation") {
+            attr.meta.require_path_only()?;
+            attrs.is_cancellation = true;
+            continue;
+        }
+        let is_option = attr.path().is_ident("option");
+        if !is_option && !attr.path().is_ident("arg") {
+            continue;
+        }
+        attrs.is_option |= is_option;
+        // `#[option]` 可以不带参数
+        if matches!(attr.meta, syn::Meta::Path(_)) {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                attrs.default = Some(literal_string(&meta.value()?.parse()?)?);
+            } else if meta.path.is_ident("ty") {
+                attrs.ty = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+            } else if meta.path.is_ident("short") && is_option {
+                attrs.short = Some(meta.value()?.parse::<syn::LitChar>()?.value());
+            } else if meta.path.is_ident("description") && is_option {
+                attrs.description = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+            } else {
+                return Err(meta.error("未知的属性"));
+            }
+            Ok(())
+        })?;
+    }
+    // 选项没有指定描述时使用文档注释
+    if attrs.is_option && attrs.description.is_none() && !docs.is_empty() {
+        attrs.description = Some(docs.join(" "));
+    }
+    Ok(attrs)
+}
+
+// 默认值的字面量，负数写作 `-1`
+fn literal_string(expr: &Expr) -> syn::Result<String> {
+    match expr {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Str(value) => Ok(value.value()),
+            Lit::Int(value) => Ok(value.base10_digits().to_string()),
+            Lit::Float(value) => Ok(value.base10_digits().to_string()),
+            Lit::Bool(value) => Ok(value.value.to_string()),
+            lit => Err(syn::Error::new(lit.span(), "不支持的默认值")),
+        },
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => {
+            Ok(format!("-{}", literal_string(&unary.expr)?))
+        }
+        expr => Err(syn::Error::new(expr.span(), "默认值必须是字面量")),
+    }
+}